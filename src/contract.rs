@@ -1,20 +1,54 @@
+use std::collections::BTreeSet;
+
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Order, to_binary
+    Addr, BankMsg, Binary, BlockInfo, CanonicalAddr, Coin, Deps, DepsMut, Empty, Env, MessageInfo,
+    Decimal, Response, StdError, StdResult, Order, Storage, Uint128, WasmMsg, from_binary, to_binary,
+    to_vec
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+use cw4::{Cw4QueryMsg, MemberResponse};
+use ripemd::Ripemd160;
+use semver::Version;
+use sha2::{Digest, Sha256};
+use cw_storage_plus::Bound;
+use cw_utils::{Duration, Expiration, Scheduled};
 
 use crate::error::ContractError;
+use crate::migrations;
+use crate::tally;
 use crate::msg::{
-    ExecuteMsg, InstantiateMsg, QueryMsg, AllPollsResponse, PollResponse, 
-    VoteResponse
+    ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, AllPollsResponse, OrderBy, PollInfo,
+    PollResponse, VoteResponse, UserAdminResponse, AllVoteUserResponse, UserVote, ReceiveMsg,
+    StakeResponse, VoiceCreditsResponse, OptionHistogram, ScoreHistogramResponse,
+    PairwiseResponse, VotingPowerResponse, Commitment, CommitmentsResponse, NonceResponse,
+    SignedVote, ConvictionResponse, OptionConvictionInfo
+};
+use crate::state::{
+    ALLOWED_CREATORS, Config, CONFIG, CreationPolicy, PENDING_ADMIN, Poll, PollLimits,
+    PollOption, PollStatus, POLLS, POLL_COUNT, Ballot, Choice, VoiceCredits, VotingMethod,
+    WeightSource, PollRules, Quorum, Threshold, ConvictionState, OptionConviction, COMMITMENTS,
+    CONVICTION, DELEGATIONS, DELEGATORS, PAIRWISE, NONCES, POLL_DELEGATIONS, POLL_DELEGATORS,
+    SCORE_HISTOGRAMS, STAKES, VOICE_CREDITS, ballots, Closing, CLOSING, DelegationCursor
 };
-use crate::state::{Config, CONFIG, Poll, POLLS, Ballot, BALLOTS};
 
 const CONTRACT_NAME: &str = "crates.io:poll-contracts";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// Límites de paginación para las queries
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+// Calificación máxima permitida en encuestas `Score`
+const MAX_SCORE: u8 = 100;
+
+// Largo máximo de una cadena de delegaciones
+const MAX_DELEGATION_DEPTH: u32 = 10;
+// Delegaciones que resuelve cada `ClosePoll`
+const MAX_DELEGATIONS_PER_CLOSE: usize = 30;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -25,8 +59,14 @@ pub fn instantiate(
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     let admin = msg.admin.unwrap_or(info.sender.to_string());
     let validated_admin = deps.api.addr_validate(&admin)?;
+    let poll_limits = msg.poll_limits.unwrap_or_default();
+    validate_poll_limits(&poll_limits)?;
     let config = Config {
-        admin: validated_admin.clone(),
+        admin: Some(validated_admin.clone()),
+        poll_limits,
+        creation_fee: vec![],
+        creation_policy: CreationPolicy::Anyone,
+        default_poll_duration: None,
     };
     CONFIG.save(deps.storage, &config)?;
     Ok(Response::new()
@@ -35,6 +75,28 @@ pub fn instantiate(
     )   
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidContractName { previous_contract: stored.contract });
+    }
+    let stored_version: Version = stored.version.parse()?;
+    let new_version: Version = CONTRACT_VERSION.parse()?;
+    if stored_version > new_version {
+        return Err(ContractError::CannotDowngrade { previous_version: stored.version });
+    }
+
+    let applied = migrations::run(deps.storage, &stored_version)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("migrations", applied.join(","))
+    )
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute (
     deps: DepsMut,
@@ -46,193 +108,5081 @@ pub fn execute (
         ExecuteMsg::CreatePoll { 
             poll_id, 
             question, 
-            options 
-        } => execute_create_poll(deps, env, info, poll_id, question, options), 
+            options,
+            start,
+            end,
+            method,
+            weight_source,
+            rules,
+            reveal_end
+        } => execute_create_poll(
+            deps, 
+            env, 
+            info, 
+            poll_id, 
+            question, 
+            options, 
+            start, 
+            end, 
+            method.unwrap_or_default(),
+            weight_source.unwrap_or_default(),
+            rules,
+            reveal_end
+        ), 
         
         ExecuteMsg::Vote { 
             poll_id, 
             vote 
-        } => execute_vote(deps, env, info, poll_id, vote),
+        } => execute_vote(deps, env, info, poll_id, Choice::Single(vote)),
+        ExecuteMsg::CastBallot { 
+            poll_id, 
+            choice 
+        } => execute_vote(deps, env, info, poll_id, choice),
+        ExecuteMsg::CommitVote { 
+            poll_id, 
+            commitment 
+        } => execute_commit_vote(deps, env, info, poll_id, commitment),
+        ExecuteMsg::RevealVote { 
+            poll_id, 
+            vote, 
+            salt 
+        } => execute_reveal_vote(deps, env, info, poll_id, vote, salt),
+        ExecuteMsg::SubmitSignedVotes { votes } => {
+            execute_submit_signed_votes(deps, env, info, votes)
+        }
         
-        ExecuteMsg::DeletePoll { poll_id } => unimplemented!(),
-        ExecuteMsg::RevokeVote { poll_id, vote } => unimplemented!(),
+        ExecuteMsg::DeletePoll { poll_id } => execute_delete_poll(deps, env, info, poll_id),
+        ExecuteMsg::RevokeVote { 
+            poll_id, 
+            vote 
+        } => execute_revoke_vote(deps, env, info, poll_id, vote),
+        ExecuteMsg::RevokeBallot { poll_id } => execute_revoke_ballot(deps, env, info, poll_id),
+        ExecuteMsg::ClosePoll { poll_id } => execute_close_poll(deps, env, info, poll_id),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::Withdraw { poll_id } => execute_withdraw(deps, env, info, poll_id),
+        ExecuteMsg::Delegate { to, poll_id } => execute_delegate(deps, env, info, to, poll_id),
+        ExecuteMsg::Undelegate { poll_id } => execute_undelegate(deps, env, info, poll_id),
+        ExecuteMsg::ProposeNewAdmin { new_admin } => {
+            execute_propose_new_admin(deps, env, info, new_admin)
+        }
+        ExecuteMsg::AcceptAdmin {  } => execute_accept_admin(deps, env, info),
+        ExecuteMsg::RenounceAdmin {  } => execute_renounce_admin(deps, env, info),
+        ExecuteMsg::UpdateConfig { 
+            poll_limits, 
+            creation_fee, 
+            creation_policy, 
+            add_creators, 
+            remove_creators, 
+            default_poll_duration 
+        } => execute_update_config(
+            deps, 
+            env, 
+            info, 
+            poll_limits, 
+            creation_fee, 
+            creation_policy, 
+            add_creators, 
+            remove_creators, 
+            default_poll_duration
+        ),
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn execute_create_poll(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    poll_id: String,
+    poll_id: Option<String>,
     question: String,
-    options: Vec<String>
+    options: Vec<String>,
+    start: Option<Scheduled>,
+    end: Option<Expiration>,
+    method: VotingMethod,
+    weight_source: WeightSource,
+    rules: Option<PollRules>,
+    reveal_end: Option<Expiration>
 ) -> Result<Response, ContractError>{
-    if options.len() > 10 {
-        return Err(ContractError::TooManyOptions {  });
+    let config = CONFIG.load(deps.storage)?;
+    if config.creation_policy == CreationPolicy::Allowlist
+        && !config.is_admin(&info.sender)
+        && !ALLOWED_CREATORS.has(deps.storage, &info.sender)
+    {
+        return Err(ContractError::Unauthorized {  });
+    }
+    check_creation_fee(&info.funds, &config.creation_fee)?;
+    validate_poll_content(&config.poll_limits, &question, &options)?;
+    validate_voting_method(&method, &weight_source, &options)?;
+    if let Some(rules) = &rules {
+        validate_poll_rules(rules, &method, &options)?;
+    }
+    match &weight_source {
+        WeightSource::Cw20Balance { token } | WeightSource::Cw20Staked { token } => {
+            deps.api.addr_validate(token)?;
+        }
+        WeightSource::Cw4Group { group } => {
+            deps.api.addr_validate(group)?;
+        }
+        WeightSource::Staked { validators: Some(validators) } => {
+            let duplicated = (0..validators.len()).any(|i| validators[..i].contains(&validators[i]));
+            if validators.is_empty() || duplicated {
+                return Err(ContractError::InvalidValidatorSet {  });
+            }
+        }
+        WeightSource::Uniform | WeightSource::NativeBalance { .. } | WeightSource::Staked { .. } => {}
+    }
+    let end = end.or_else(|| {
+        config
+            .default_poll_duration
+            .map(|duration| default_poll_end(&env.block, start, duration))
+    });
+    validate_poll_window(&env.block, start, end)?;
+    if let Some(reveal_end) = reveal_end {
+        validate_reveal_window(end, reveal_end, &method, &weight_source)?;
     }
-    let mut opts: Vec<(String, u64)> = vec![];
+    let poll_id = match poll_id {
+        Some(poll_id) => {
+            if POLLS.has(deps.storage, poll_id.clone()) {
+                return Err(ContractError::PollAlreadyExists {  });
+            }
+            poll_id
+        }
+        None => next_poll_id(deps.storage)?,
+    };
+
+    let mut opts: Vec<PollOption> = vec![];
     for option in options {
-        opts.push((option, 0));
+        opts.push(PollOption { option, votes: Uint128::zero() });
     }
 
     let poll = Poll {
-        creator: info.sender,
+        creator: info.sender.clone(),
         question, 
-        options: opts
+        options: opts,
+        start,
+        end,
+        result: None,
+        snapshot_height: weight_source.uses_snapshot().then_some(env.block.height),
+        method,
+        weight_source,
+        voting_weight: Uint128::zero(),
+        rules,
+        reveal_end
+    };
+
+    POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+
+    // La tarifa de creación se transfiere al admin
+    let mut response = Response::new();
+    if !config.creation_fee.is_empty() {
+        let admin = config.admin.ok_or(ContractError::CreationFeeWithoutAdmin {  })?;
+        response = response.add_message(BankMsg::Send {
+            to_address: admin.to_string(),
+            amount: config.creation_fee,
+        });
+    }
+    Ok(response
+        .add_attribute("action", "create_poll")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("creator", info.sender.to_string())
+    )
+}
+
+// Los fondos deben coincidir exactamente con la tarifa: lo que sobre quedaría
+// bloqueado en el contrato
+fn check_creation_fee(funds: &[Coin], fee: &[Coin]) -> Result<(), ContractError> {
+    let sum = |coins: &[Coin], denom: &str| -> Uint128 {
+        coins.iter().filter(|c| c.denom == denom).map(|c| c.amount).sum()
+    };
+    for coin in fee {
+        if coin.amount > sum(funds, &coin.denom) {
+            let fee = fee.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(",");
+            return Err(ContractError::InsufficientCreationFee { fee });
+        }
+    }
+    if funds.iter().any(|fund| sum(funds, &fund.denom) > sum(fee, &fund.denom)) {
+        return Err(ContractError::UnexpectedFunds {  });
+    }
+    Ok(())
+}
+
+// Sin `end` explícito, la duración por defecto se cuenta desde el inicio de la
+// encuesta si usa la misma unidad, o si no desde el bloque de creación
+fn default_poll_end(block: &BlockInfo, start: Option<Scheduled>, duration: Duration) -> Expiration {
+    match (start, duration) {
+        (Some(Scheduled::AtHeight(height)), Duration::Height(blocks)) => {
+            Expiration::AtHeight(height + blocks)
+        }
+        (Some(Scheduled::AtTime(time)), Duration::Time(seconds)) => {
+            Expiration::AtTime(time.plus_seconds(seconds))
+        }
+        _ => duration.after(block),
+    }
+}
+
+fn validate_poll_limits(limits: &PollLimits) -> Result<(), ContractError> {
+    if limits.min_options < 2
+        || limits.min_options > limits.max_options
+        || limits.max_question_length == 0
+        || limits.max_option_length == 0
+    {
+        return Err(ContractError::InvalidPollLimits {  });
+    }
+    Ok(())
+}
+
+fn validate_poll_content(
+    limits: &PollLimits,
+    question: &str,
+    options: &[String]
+) -> Result<(), ContractError> {
+    if question.trim().is_empty() {
+        return Err(ContractError::EmptyQuestion {  });
+    }
+    if question.len() > limits.max_question_length as usize {
+        return Err(ContractError::QuestionTooLong {  });
+    }
+    if options.len() < limits.min_options as usize {
+        return Err(ContractError::TooFewOptions {  });
+    }
+    if options.len() > limits.max_options as usize {
+        return Err(ContractError::TooManyOptions {  });
+    }
+    for (i, option) in options.iter().enumerate() {
+        if option.trim().is_empty() {
+            return Err(ContractError::EmptyOption {  });
+        }
+        if option.len() > limits.max_option_length as usize {
+            return Err(ContractError::OptionTooLong { option: option.clone() });
+        }
+        if limits.unique_options && options[..i].contains(option) {
+            return Err(ContractError::DuplicateOption { option: option.clone() });
+        }
+    }
+    Ok(())
+}
+
+fn validate_voting_method(
+    method: &VotingMethod,
+    weight_source: &WeightSource,
+    options: &[String],
+) -> Result<(), ContractError> {
+    // Las papeletas `Ranked` y `Score` nombran cada opción una sola vez, y sus
+    // recuentos se guardan por nombre, aunque `PollLimits` admita repetidas
+    if let VotingMethod::Ranked | VotingMethod::Score { .. } = method {
+        if let Some(i) = (0..options.len()).find(|i| options[..*i].contains(&options[*i])) {
+            return Err(ContractError::DuplicateOption { option: options[i].clone() });
+        }
+    }
+    match method {
+        VotingMethod::SingleChoice | VotingMethod::Ranked => {}
+        VotingMethod::Approval { max_selections } => {
+            if let Some(max_selections) = max_selections {
+                if *max_selections == 0 || *max_selections as usize > options.len() {
+                    return Err(ContractError::InvalidVotingMethod {  });
+                }
+            }
+        }
+        VotingMethod::Score { max_score } => {
+            if *max_score == 0 || *max_score > MAX_SCORE {
+                return Err(ContractError::InvalidVotingMethod {  });
+            }
+        }
+        // La convicción se acumula mientras los tokens siguen comprometidos:
+        // solo los depósitos bloqueados impiden mover los tokens y votar otra vez
+        VotingMethod::Conviction { decay, threshold } => {
+            let staked = matches!(weight_source, WeightSource::Cw20Staked { .. });
+            if decay.is_zero() || *decay >= Decimal::one() || threshold.is_zero() || !staked {
+                return Err(ContractError::InvalidVotingMethod {  });
+            }
+        }
+        // El presupuesto de créditos ya es el peso del votante
+        VotingMethod::Quadratic { credits } => {
+            let valid = match credits {
+                VoiceCredits::Fixed { amount } => *amount > 0,
+                VoiceCredits::Purchased { price } => !price.amount.is_zero(),
+            };
+            if !valid || *weight_source != WeightSource::Uniform {
+                return Err(ContractError::InvalidVotingMethod {  });
+            }
+        }
+    }
+    Ok(())
+}
+
+// Una encuesta secreta necesita `end` y revela después de él, en la misma
+// unidad. Solo admite voto de opción única y pesos que no se emitan al votar
+fn validate_reveal_window(
+    end: Option<Expiration>,
+    reveal_end: Expiration,
+    method: &VotingMethod,
+    weight_source: &WeightSource,
+) -> Result<(), ContractError> {
+    let valid = match (end, reveal_end) {
+        (Some(Expiration::AtHeight(end)), Expiration::AtHeight(reveal_end)) => end < reveal_end,
+        (Some(Expiration::AtTime(end)), Expiration::AtTime(reveal_end)) => end < reveal_end,
+        _ => false,
+    };
+    if !valid {
+        return Err(ContractError::InvalidRevealWindow {  });
+    }
+    let staked = matches!(weight_source, WeightSource::Cw20Staked { .. });
+    if *method != VotingMethod::SingleChoice || staked {
+        return Err(ContractError::InvalidVotingMethod {  });
+    }
+    Ok(())
+}
+
+// Las fracciones deben estar en (0, 1] y las opciones de abstención y veto
+// deben existir y ser distintas
+// Las reglas comparan los votos de cada opción con el peso de los votantes, lo
+// que solo vale si cada votante suma su peso a una única opción. En `Ranked` el
+// ganador sale además de la segunda vuelta, no de los votos de las opciones
+fn validate_poll_rules(
+    rules: &PollRules,
+    method: &VotingMethod,
+    options: &[String],
+) -> Result<(), ContractError> {
+    if *method != VotingMethod::SingleChoice {
+        return Err(ContractError::InvalidPollRules {  });
+    }
+    let valid_fraction = |fraction: &Decimal| !fraction.is_zero() && *fraction <= Decimal::one();
+    let quorum_valid = match &rules.quorum {
+        Some(Quorum::Absolute { weight }) => !weight.is_zero(),
+        Some(Quorum::Percentage { percentage, total_weight }) => {
+            valid_fraction(percentage) && !total_weight.is_zero()
+        }
+        None => true,
+    };
+    let threshold_valid = match &rules.threshold {
+        Threshold::Fraction { fraction } => valid_fraction(fraction),
+        Threshold::Majority {} | Threshold::Supermajority {} => true,
+    };
+    let abstain = rules.abstain_option.as_ref();
+    let veto = rules.veto.as_ref().map(|veto| &veto.option);
+    let options_valid = abstain.iter().chain(veto.iter()).all(|option| options.contains(option))
+        && (abstain.is_none() || abstain != veto);
+    let veto_valid = match &rules.veto {
+        Some(veto) => valid_fraction(&veto.threshold),
+        None => true,
     };
+    if !(quorum_valid && threshold_valid && options_valid && veto_valid) {
+        return Err(ContractError::InvalidPollRules {  });
+    }
+    Ok(())
+}
+
+// La encuesta no puede terminar antes de crearse ni antes de empezar.
+// Solo se comparan start y end cuando usan la misma unidad (altura o tiempo)
+fn validate_poll_window(
+    block: &BlockInfo,
+    start: Option<Scheduled>,
+    end: Option<Expiration>
+) -> Result<(), ContractError> {
+    if let Some(end) = end {
+        if end.is_expired(block) {
+            return Err(ContractError::InvalidPollWindow {  });
+        }
+        let invalid = match (start, end) {
+            (Some(Scheduled::AtHeight(start)), Expiration::AtHeight(end)) => start >= end,
+            (Some(Scheduled::AtTime(start)), Expiration::AtTime(end)) => start >= end,
+            _ => false,
+        };
+        if invalid {
+            return Err(ContractError::InvalidPollWindow {  });
+        }
+    }
+    Ok(())
+}
 
-    POLLS.save(deps.storage, poll_id, &poll)?;
-    Ok(Response::new())
+// Genera el siguiente id numérico libre, saltando los ids que ya fueron
+// elegidos manualmente por otros usuarios
+fn next_poll_id(storage: &mut dyn Storage) -> StdResult<String> {
+    let mut count = POLL_COUNT.may_load(storage)?.unwrap_or_default();
+    let poll_id = loop {
+        count += 1;
+        let candidate = count.to_string();
+        if !POLLS.has(storage, candidate.clone()) {
+            break candidate;
+        }
+    };
+    POLL_COUNT.save(storage, &count)?;
+    Ok(poll_id)
 }
 
 fn execute_vote(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
+    info: MessageInfo,
+    poll_id: String,
+    choice: Choice,
+) -> Result<Response, ContractError> {
+    process_vote(deps, &env.block, &poll_id, &info.sender, &info.funds, choice)?;
+    Ok(Response::new()
+        .add_attribute("action", "vote")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("voter", info.sender.to_string())
+    )
+}
+
+// Camino común de los votos directos y los firmados: valida la encuesta, cobra
+// los créditos de voz y registra la papeleta de `voter`
+fn process_vote(
+    deps: DepsMut,
+    block: &BlockInfo,
+    poll_id: &str,
+    voter: &Addr,
+    funds: &[Coin],
+    choice: Choice,
+) -> Result<(), ContractError> {
+    let mut poll = POLLS.may_load(deps.storage, poll_id.to_string())?
+        .ok_or(ContractError::PollNotFound {  })?;
+    ensure_poll_open(&poll, block)?;
+    if poll.reveal_end.is_some() {
+        return Err(ContractError::SecretPoll {  });
+    }
+    let weight = voting_power(deps.as_ref(), poll_id, &poll, voter)?;
+    charge_voice_credits(deps.storage, poll_id, &poll, voter, funds, &choice)?;
+    cast_ballot(deps.storage, poll_id, &mut poll, voter, choice, weight, block.height)?;
+
+    // Guardamos la actualización de la encuesta
+    POLLS.save(deps.storage, poll_id.to_string(), &poll)?;
+    Ok(())
+}
+
+fn execute_submit_signed_votes(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    votes: Vec<SignedVote>,
+) -> Result<Response, ContractError> {
+    if votes.is_empty() {
+        return Err(ContractError::EmptySignedVotes {  });
+    }
+    // El relayer no aporta fondos en nombre de los votantes
+    if !info.funds.is_empty() {
+        return Err(ContractError::UnexpectedFunds {  });
+    }
+    let mut response = Response::new().add_attribute("action", "submit_signed_votes");
+    for vote in votes {
+        let voter = verify_signed_vote(deps.as_ref(), &env, &vote)?;
+        NONCES.save(deps.storage, &voter, &(vote.payload.nonce + 1))?;
+        process_vote(
+            deps.branch(),
+            &env.block,
+            &vote.payload.poll_id,
+            &voter,
+            &[],
+            vote.payload.choice,
+        )?;
+        response = response
+            .add_attribute("voter", voter.to_string())
+            .add_attribute("poll_id", vote.payload.poll_id);
+    }
+    Ok(response)
+}
+
+// Comprueba la firma, la cadena, el contrato y el nonce de un voto firmado y devuelve la
+// dirección del votante, derivada de su clave pública
+fn verify_signed_vote(deps: Deps, env: &Env, vote: &SignedVote) -> Result<Addr, ContractError> {
+    if vote.pubkey.len() != 33 {
+        return Err(ContractError::InvalidPubkey {  });
+    }
+    let hash = Sha256::digest(&to_vec(&vote.payload)?);
+    if !deps.api.secp256k1_verify(&hash, &vote.signature, &vote.pubkey)
+        .map_err(|_| ContractError::InvalidSignature {  })? {
+        return Err(ContractError::InvalidSignature {  });
+    }
+    if vote.payload.chain_id != env.block.chain_id {
+        return Err(ContractError::InvalidChainId {  });
+    }
+    if vote.payload.contract != env.contract.address.as_str() {
+        return Err(ContractError::InvalidContract {  });
+    }
+
+    // Misma derivación que las cuentas de Cosmos: ripemd160(sha256(pubkey))
+    let canonical = Ripemd160::digest(Sha256::digest(vote.pubkey.as_slice()));
+    let voter = deps.api.addr_humanize(&CanonicalAddr::from(canonical.to_vec()))?;
+    let voter = deps.api.addr_validate(voter.as_str())?;
+    let expected = NONCES.may_load(deps.storage, &voter)?.unwrap_or_default();
+    if vote.payload.nonce != expected {
+        return Err(ContractError::InvalidNonce { expected });
+    }
+    Ok(voter)
+}
+
+fn execute_commit_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: String,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    let poll = POLLS.may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {  })?;
+    if poll.reveal_end.is_none() {
+        return Err(ContractError::NotSecretPoll {  });
+    }
+    ensure_poll_open(&poll, &env.block)?;
+    if commitment.len() != 32 {
+        return Err(ContractError::InvalidCommitment {  });
+    }
+
+    // Un nuevo compromiso reemplaza al anterior
+    COMMITMENTS.save(deps.storage, (&poll_id, &info.sender), &commitment)?;
+    Ok(Response::new()
+        .add_attribute("action", "commit_vote")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("voter", info.sender.to_string())
+    )
+}
+
+fn execute_reveal_vote(
+    deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     poll_id: String,
     vote: String,
+    salt: String,
 ) -> Result<Response, ContractError> {
-    let poll = POLLS.may_load(deps.storage, poll_id.clone())?;
-    match poll {
-        Some(mut poll) => {
-            BALLOTS.update(
-                deps.storage,
-                (info.sender, poll_id.clone()),
-                |ballot| -> StdResult<Ballot> {
-                    match ballot {
-                        Some(ballot) => {
-                            // existe un voto anterior, revocamos el voto anterior
-                            // Buscamos la posición
-                            let position_of_old_vote = poll
-                                .options
-                                .iter()
-                                .position(|option| option.0 == ballot.option)
-                                .unwrap();
-                            // decrementamos el conteo de voto de la opción
-                            poll.options[position_of_old_vote].1 -= 1;
-                            // Actualización del voto
-                            Ok(Ballot {option: vote.clone()})
-                        }
-                        None => {
-                            // No existe un voto anterior, se agrega el ballot
-                            Ok(Ballot {option:vote.clone()})
-                        }
-                    }
-                },
-            )?;
-            // Encontramos la posición del voto y agregamos el contador en 1
-            let position = poll
-                .options
-                .iter()
-                .position(|option| option.0 == vote);
-            if position.is_none(){
-                return Err(ContractError::OptionNotFound {  });
+    let mut poll = POLLS.may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {  })?;
+    if poll.status(&env.block) != PollStatus::Reveal {
+        return Err(ContractError::NotRevealPhase {  });
+    }
+    let commitment = COMMITMENTS.may_load(deps.storage, (&poll_id, &info.sender))?
+        .ok_or(ContractError::CommitmentNotFound {  })?;
+    if commitment_hash(&poll_id, &info.sender, &vote, &salt)[..] != commitment[..] {
+        return Err(ContractError::InvalidReveal {  });
+    }
+
+    COMMITMENTS.remove(deps.storage, (&poll_id, &info.sender));
+    let weight = voting_power(deps.as_ref(), &poll_id, &poll, &info.sender)?;
+    let choice = Choice::Single(vote.clone());
+    cast_ballot(
+        deps.storage,
+        &poll_id,
+        &mut poll,
+        &info.sender,
+        choice,
+        weight,
+        env.block.height,
+    )?;
+    POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+    Ok(Response::new()
+        .add_attribute("action", "reveal_vote")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("voter", info.sender.to_string())
+        .add_attribute("vote", vote)
+    )
+}
+
+// Hash que se compromete en `CommitVote`. La encuesta, el votante y la opción
+// llevan delante su largo en 4 bytes big-endian para que ninguna opción pueda
+// revelarse como otra, ni el compromiso copiarse a otro votante o encuesta
+fn commitment_hash(poll_id: &str, voter: &Addr, vote: &str, salt: &str) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    for field in [poll_id, voter.as_str(), vote] {
+        hasher.update((field.len() as u32).to_be_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher.update(salt.as_bytes());
+    hasher.finalize().to_vec()
+}
+
+// Descuenta el costo del voto del presupuesto de créditos de voz. Los créditos
+// comprados quedan en el contrato hasta que la encuesta se cierra
+fn charge_voice_credits(
+    storage: &mut dyn Storage,
+    poll_id: &str,
+    poll: &Poll,
+    voter: &Addr,
+    funds: &[Coin],
+    choice: &Choice,
+) -> Result<(), ContractError> {
+    let price = poll.method.credit_price();
+    if funds.iter().any(|coin| Some(&coin.denom) != price.map(|price| &price.denom)) {
+        return Err(ContractError::UnexpectedFunds {  });
+    }
+    let credits = match &poll.method {
+        VotingMethod::Quadratic { credits } => credits,
+        _ => return Ok(()),
+    };
+    let budget = match credits {
+        VoiceCredits::Fixed { amount } => *amount,
+        VoiceCredits::Purchased { price } => {
+            let paid: Uint128 = funds.iter().map(|coin| coin.amount).sum();
+            if !(paid % price.amount).is_zero() {
+                return Err(ContractError::InvalidCreditPurchase {  });
             }
-            let position = position.unwrap();
-            poll.options[position].1 += 1;
+            let bought = u64::try_from((paid / price.amount).u128())
+                .map_err(|_| ContractError::InvalidCreditPurchase {  })?;
+            VOICE_CREDITS.update(
+                storage,
+                (poll_id, voter),
+                |credits| -> StdResult<_> {
+                    credits.unwrap_or_default()
+                        .checked_add(bought)
+                        .ok_or_else(|| StdError::generic_err("voice credits overflow"))
+                },
+            )?
+        }
+    };
+    let cost = quadratic_cost(choice)?;
+    if cost > Uint128::from(budget) {
+        return Err(ContractError::InsufficientCredits { cost, budget: budget.into() });
+    }
+    Ok(())
+}
 
-            // Guardamos la actualización de la encuesta
-            POLLS.save(deps.storage, poll_id, &poll)?;
-            Ok(Response::new())
-        },
-        None => Err(ContractError::PollNotFound {  }),
+// Créditos que cuesta una elección cuadrática: la suma de los votos al cuadrado
+fn quadratic_cost(choice: &Choice) -> StdResult<Uint128> {
+    let mut cost = Uint128::zero();
+    if let Choice::Quadratic(votes) = choice {
+        for vote in votes {
+            let votes = Uint128::from(vote.votes);
+            cost = cost.checked_add(votes.checked_mul(votes)?)?;
+        }
     }
+    Ok(cost)
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::AllPolls {  } => query_all_polls(deps, env),
-        QueryMsg::Poll { poll_id  } => query_poll(deps, env, poll_id),
-        QueryMsg::Vote { address, poll_id } => query_vote(deps, env, address, poll_id),
-        QueryMsg::ConfigUser { } => unimplemented!(),
-        QueryMsg::AllVoteUser { address } => unimplemented!(),
+// Peso del votante según el origen configurado en la encuesta
+fn voting_power(
+    deps: Deps,
+    poll_id: &str,
+    poll: &Poll,
+    voter: &Addr,
+) -> Result<Uint128, ContractError> {
+    let weight = match &poll.weight_source {
+        WeightSource::Uniform => Uint128::new(1),
+        WeightSource::NativeBalance { denom } => {
+            deps.querier.query_balance(voter, denom)?.amount
+        }
+        WeightSource::Cw20Balance { token } => {
+            let res: BalanceResponse = deps.querier.query_wasm_smart(
+                token,
+                &Cw20QueryMsg::Balance { address: voter.to_string() },
+            )?;
+            res.balance
+        }
+        // El peso del miembro queda fijado a la altura de creación de la encuesta
+        WeightSource::Cw4Group { group } => {
+            let res: MemberResponse = deps.querier.query_wasm_smart(
+                group,
+                &Cw4QueryMsg::Member {
+                    addr: voter.to_string(),
+                    at_height: poll.snapshot_height,
+                },
+            )?;
+            Uint128::from(res.weight.ok_or(ContractError::NotEligible {  })?)
+        }
+        // Cambiar la elección sin depositar más tokens mantiene el monto bloqueado
+        WeightSource::Cw20Staked { .. } => {
+            STAKES.may_load(deps.storage, (poll_id, voter))?.unwrap_or_default()
+        }
+        WeightSource::Staked { validators } => {
+            let denom = deps.querier.query_bonded_denom()?;
+            deps.querier
+                .query_all_delegations(voter)?
+                .into_iter()
+                .filter(|delegation| delegation.amount.denom == denom)
+                .filter(|delegation| match validators {
+                    Some(set) => set.contains(&delegation.validator),
+                    None => true,
+                })
+                .map(|delegation| delegation.amount.amount)
+                .sum()
+        }
+    };
+    if weight.is_zero() {
+        return Err(ContractError::NoVotingPower {  });
     }
+    Ok(weight)
 }
 
-fn query_all_polls(deps: Deps, _env: Env) -> StdResult<Binary> {
-    let polls = POLLS
-        .range(deps.storage, None, None, Order::Ascending)
-        .map(|p| Ok(p?.1))
-        .collect::<StdResult<Vec<_>>>()?;
+// Camino común de conteo: valida la elección, descuenta el voto anterior del
+// votante (si existe) con su peso guardado y suma el nuevo. No guarda la encuesta
+fn cast_ballot(
+    storage: &mut dyn Storage,
+    poll_id: &str,
+    poll: &mut Poll,
+    voter: &Addr,
+    choice: Choice,
+    weight: Uint128,
+    height: u64,
+) -> Result<(), ContractError> {
+    validate_choice(poll, &choice)?;
+    accrue_conviction(storage, poll_id, poll, height)?;
+    let key = (voter.clone(), poll_id.to_string());
+    if let Some(old_ballot) = ballots().may_load(storage, key.clone())? {
+        tally_ballot(storage, poll_id, poll, &old_ballot.choice, old_ballot.weight, false)?;
+    }
+    tally_ballot(storage, poll_id, poll, &choice, weight, true)?;
+    let since_height = matches!(poll.method, VotingMethod::Conviction { .. }).then_some(height);
+    ballots().save(storage, key, &Ballot { choice, weight, delegated: Uint128::zero(), since_height })?;
+    Ok(())
+}
 
-    to_binary(&AllPollsResponse {polls})
+// Suma (`add`) o descuenta un voto en todos los conteos de la encuesta
+fn tally_ballot(
+    storage: &mut dyn Storage,
+    poll_id: &str,
+    poll: &mut Poll,
+    choice: &Choice,
+    weight: Uint128,
+    add: bool,
+) -> StdResult<()> {
+    poll.apply_choice(choice, weight, add);
+    update_score_histograms(storage, poll_id, poll, choice, weight, add)?;
+    update_pairwise(storage, poll_id, poll, choice, weight, add)
 }
 
-fn query_poll(deps: Deps, _env: Env, poll_id: String) -> StdResult<Binary> {
-    let poll = POLLS.may_load(deps.storage, poll_id)?;
-    to_binary(&PollResponse {poll})
+// En encuestas `Ranked` suma (`add`) o descuenta el peso del voto en cada par
+// de opciones. Las opciones ordenadas se prefieren a las no ordenadas
+fn update_pairwise(
+    storage: &mut dyn Storage,
+    poll_id: &str,
+    poll: &Poll,
+    choice: &Choice,
+    weight: Uint128,
+    add: bool,
+) -> StdResult<()> {
+    let ranking = match (&poll.method, choice) {
+        (VotingMethod::Ranked, Choice::Ranked(ranking)) => ranking,
+        _ => return Ok(()),
+    };
+    let n = poll.options.len();
+    let positions: Vec<Option<usize>> = poll.options
+        .iter()
+        .map(|option| ranking.iter().position(|o| *o == option.option))
+        .collect();
+    let mut matrix = PAIRWISE.may_load(storage, poll_id)?
+        .unwrap_or_else(|| vec![vec![Uint128::zero(); n]; n]);
+    for (i, row) in matrix.iter_mut().enumerate() {
+        for (j, count) in row.iter_mut().enumerate() {
+            let preferred = match (positions[i], positions[j]) {
+                (Some(a), Some(b)) => a < b,
+                (Some(_), None) => true,
+                _ => false,
+            };
+            if preferred {
+                if add {
+                    *count += weight;
+                } else {
+                    *count -= weight;
+                }
+            }
+        }
+    }
+    PAIRWISE.save(storage, poll_id, &matrix)
 }
 
-fn query_vote(deps: Deps, _env: Env, address: String, poll_id: String) -> StdResult<Binary> {
-    let validated_address = deps.api.addr_validate(&address).unwrap();
-    let vote = BALLOTS.may_load(deps.storage, (validated_address, poll_id))?;
+// Matriz de preferencias por pares de la encuesta; ceros si aún no hay votos
+fn load_pairwise(storage: &dyn Storage, poll_id: &str, poll: &Poll) -> StdResult<Vec<Vec<Uint128>>> {
+    let n = poll.options.len();
+    Ok(PAIRWISE.may_load(storage, poll_id)?.unwrap_or_else(|| vec![vec![Uint128::zero(); n]; n]))
+}
 
-    to_binary(&VoteResponse { vote})
+// Convicción de cada opción llevada hasta `height`. El respaldo de cada opción
+// son sus votos actuales, que no cambian desde la última actualización
+fn load_conviction(
+    storage: &dyn Storage,
+    poll_id: &str,
+    poll: &Poll,
+    height: u64,
+) -> StdResult<ConvictionState> {
+    let mut state = CONVICTION.may_load(storage, poll_id)?.unwrap_or_else(|| ConvictionState {
+        updated_at: height,
+        options: vec![OptionConviction::default(); poll.options.len()],
+    });
+    let (decay, threshold) = match &poll.method {
+        VotingMethod::Conviction { decay, threshold } => (*decay, *threshold),
+        _ => return Ok(state),
+    };
+    let blocks = height.saturating_sub(state.updated_at);
+    for (current, option) in state.options.iter_mut().zip(poll.options.iter()) {
+        if current.passed_at.is_none() {
+            current.passed_at =
+                tally::threshold_crossing(current.conviction, option.votes, decay, blocks, threshold)
+                    .map(|block| state.updated_at + block);
+        }
+        current.conviction = tally::conviction(current.conviction, option.votes, decay, blocks);
+    }
+    state.updated_at = state.updated_at.max(height);
+    Ok(state)
 }
 
-// fn query_config_user(deps: Deps, _env: Env) -> StdResult<Binary> {
-//     let user_admin = CONFIG.may_load(deps.storage)?;
-//     let user_admin = user_admin.unwrap();
-//     to_binary(&UserAdminResponse {&'a user_admin})
-// }
+// En encuestas `Conviction` guarda la convicción acumulada hasta `height`
+// antes de que cambien los votos
+fn accrue_conviction(
+    storage: &mut dyn Storage,
+    poll_id: &str,
+    poll: &Poll,
+    height: u64,
+) -> StdResult<()> {
+    if let VotingMethod::Conviction { .. } = poll.method {
+        let state = load_conviction(storage, poll_id, poll, height)?;
+        CONVICTION.save(storage, poll_id, &state)?;
+    }
+    Ok(())
+}
 
-#[cfg(test)]
-mod tests {
-    use std::vec;
+// En encuestas `Score` suma (`add`) o descuenta el peso del voto en la
+// distribución de calificaciones de cada opción
+fn update_score_histograms(
+    storage: &mut dyn Storage,
+    poll_id: &str,
+    poll: &Poll,
+    choice: &Choice,
+    weight: Uint128,
+    add: bool,
+) -> StdResult<()> {
+    let (max_score, scores) = match (&poll.method, choice) {
+        (VotingMethod::Score { max_score }, Choice::Score(scores)) => (*max_score, scores),
+        _ => return Ok(()),
+    };
+    for score in scores {
+        let key = (poll_id, score.option.as_str());
+        let mut histogram = SCORE_HISTOGRAMS.may_load(storage, key)?
+            .unwrap_or_else(|| vec![Uint128::zero(); max_score as usize + 1]);
+        let count = &mut histogram[score.score as usize];
+        if add {
+            *count += weight;
+        } else {
+            *count -= weight;
+        }
+        SCORE_HISTOGRAMS.save(storage, key, &histogram)?;
+    }
+    Ok(())
+}
 
-    use cosmwasm_std::{attr, from_binary};
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use crate::contract::{instantiate, execute};
-    use crate::msg::{
-        InstantiateMsg, ExecuteMsg, AllPollsResponse, PollResponse, QueryMsg,
-        VoteResponse
+// Distribución de calificaciones de cada opción, en el orden de la encuesta
+fn load_score_histograms(
+    storage: &dyn Storage,
+    poll_id: &str,
+    poll: &Poll,
+) -> StdResult<Vec<Vec<Uint128>>> {
+    let max_score = match poll.method {
+        VotingMethod::Score { max_score } => max_score,
+        _ => return Ok(vec![]),
     };
-    use crate::state::Poll;
+    poll.options
+        .iter()
+        .map(|option| {
+            Ok(SCORE_HISTOGRAMS.may_load(storage, (poll_id, option.option.as_str()))?
+                .unwrap_or_else(|| vec![Uint128::zero(); max_score as usize + 1]))
+        })
+        .collect()
+}
 
-    use super::query;
+// Deposita los tokens cw20 recibidos como peso del voto del remitente original
+fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let voter = deps.api.addr_validate(&wrapper.sender)?;
+    match from_binary(&wrapper.msg)? {
+        ReceiveMsg::Vote { poll_id, choice } => {
+            let mut poll = POLLS.may_load(deps.storage, poll_id.clone())?
+                .ok_or(ContractError::PollNotFound {  })?;
+            match &poll.weight_source {
+                WeightSource::Cw20Staked { token } if *token == info.sender => {}
+                _ => return Err(ContractError::InvalidToken {  }),
+            }
+            ensure_poll_open(&poll, &env.block)?;
 
-    pub const ADDR1: &str = "addr1";
-    pub const ADDR2: &str = "addr2";
-    #[test]
-    fn test_instantiate(){
-        let mut deps = mock_dependencies();
-        let env = mock_env();
-        let info = mock_info(ADDR1, &vec![]);
-        
-        // Create a message where we (the sender) will be an admin
-        let msg = InstantiateMsg {admin: None};
-        let res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+            let stake = STAKES.update(
+                deps.storage,
+                (&poll_id, &voter),
+                |stake| -> StdResult<_> { Ok(stake.unwrap_or_default() + wrapper.amount) },
+            )?;
+            cast_ballot(
+                deps.storage,
+                &poll_id,
+                &mut poll,
+                &voter,
+                choice,
+                stake,
+                env.block.height,
+            )?;
+            POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+            Ok(Response::new()
+                .add_attribute("action", "vote")
+                .add_attribute("poll_id", poll_id)
+                .add_attribute("voter", voter.to_string())
+                .add_attribute("staked", stake.to_string())
+            )
+        }
+    }
+}
 
-        assert_eq!(
-            res.attributes,
-            vec![attr("action", "instantiate"), attr("admin", ADDR1)]
-        )
+fn execute_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: String,
+) -> Result<Response, ContractError> {
+    let poll = POLLS.may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {  })?;
+    if poll.status(&env.block) != PollStatus::Closed {
+        return Err(ContractError::PollNotClosed {  });
     }
+    let amount = STAKES.may_load(deps.storage, (&poll_id, &info.sender))?
+        .ok_or(ContractError::NothingToWithdraw {  })?;
+    STAKES.remove(deps.storage, (&poll_id, &info.sender));
 
-    #[test]
-    fn test_instantiate_with_admin(){
-        let mut deps = mock_dependencies();
-        let env = mock_env();
-        let info = mock_info(ADDR1, &vec![]);
-        
-        // Create a message where admin is ADDR2
-        let msg = InstantiateMsg {admin: Some(ADDR2.to_string())};
-        let res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+    // Solo las encuestas `Cw20Staked` tienen depósitos
+    let token = poll.weight_source.cw20_token().ok_or(ContractError::NothingToWithdraw {  })?;
+    Ok(Response::new()
+        .add_message(cw20_transfer(token, &info.sender, amount)?)
+        .add_attribute("action", "withdraw")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("voter", info.sender.to_string())
+        .add_attribute("amount", amount.to_string())
+    )
+}
+
+fn cw20_transfer(token: &str, recipient: &Addr, amount: Uint128) -> StdResult<WasmMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient: recipient.to_string(), amount })?,
+        funds: vec![],
+    })
+}
+
+fn validate_choice(poll: &Poll, choice: &Choice) -> Result<(), ContractError> {
+    match (&poll.method, choice) {
+        (VotingMethod::SingleChoice | VotingMethod::Conviction { .. }, Choice::Single(_)) => {}
+        (VotingMethod::Approval { max_selections }, Choice::Approval(options)) => {
+            if options.is_empty() {
+                return Err(ContractError::EmptyBallot {  });
+            }
+            if let Some(max_selections) = max_selections {
+                if options.len() > *max_selections as usize {
+                    return Err(ContractError::TooManySelections {  });
+                }
+            }
+            ensure_unique_selections(options)?;
+        }
+        (VotingMethod::Ranked, Choice::Ranked(ranking)) => {
+            if ranking.is_empty() {
+                return Err(ContractError::EmptyBallot {  });
+            }
+            ensure_unique_selections(ranking)?;
+        }
+        (VotingMethod::Score { max_score }, Choice::Score(scores)) => {
+            if let Some(score) = scores.iter().find(|score| score.score > *max_score) {
+                return Err(ContractError::InvalidScore { option: score.option.clone() });
+            }
+            let options: Vec<String> = scores.iter().map(|score| score.option.clone()).collect();
+            ensure_unique_selections(&options)?;
+            // Sin repetidos y con opciones existentes, basta comparar la cantidad
+            if scores.len() != poll.options.len() {
+                return Err(ContractError::IncompleteBallot {  });
+            }
+        }
+        (VotingMethod::Quadratic { .. }, Choice::Quadratic(votes)) => {
+            if votes.is_empty() {
+                return Err(ContractError::EmptyBallot {  });
+            }
+            if let Some(vote) = votes.iter().find(|vote| vote.votes == 0) {
+                return Err(ContractError::InvalidVoteCount { option: vote.option.clone() });
+            }
+            let options: Vec<String> = votes.iter().map(|vote| vote.option.clone()).collect();
+            ensure_unique_selections(&options)?;
+        }
+        _ => return Err(ContractError::InvalidBallotType {  }),
+    }
+    for option in choice.options() {
+        if !poll.options.iter().any(|o| &o.option == option) {
+            return Err(ContractError::OptionNotFound {  });
+        }
+    }
+    Ok(())
+}
+
+// Solo se aceptan o retiran votos mientras la encuesta está abierta
+fn ensure_poll_open(poll: &Poll, block: &BlockInfo) -> Result<(), ContractError> {
+    match poll.status(block) {
+        PollStatus::Pending => Err(ContractError::PollNotStarted {  }),
+        PollStatus::Reveal | PollStatus::Closed => Err(ContractError::PollClosed {  }),
+        PollStatus::Open => Ok(()),
+    }
+}
+
+fn ensure_unique_selections(options: &[String]) -> Result<(), ContractError> {
+    for (i, option) in options.iter().enumerate() {
+        if options[..i].contains(option) {
+            return Err(ContractError::DuplicateSelection { option: option.clone() });
+        }
+    }
+    Ok(())
+}
+
+fn execute_revoke_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: String,
+    vote: String,
+) -> Result<Response, ContractError> {
+    let mut poll = POLLS.may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {  })?;
+    ensure_poll_open(&poll, &env.block)?;
+    let key = (info.sender.clone(), poll_id.clone());
+    let ballot = ballots().may_load(deps.storage, key)?
+        .ok_or(ContractError::BallotNotFound {  })?;
+    if ballot.choice != Choice::Single(vote.clone()) {
+        return Err(ContractError::VoteMismatch {  });
+    }
+
+    remove_ballot(deps.storage, &poll_id, &mut poll, &info.sender, env.block.height)?;
+    POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+    Ok(Response::new()
+        .add_attribute("action", "revoke_vote")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("voter", info.sender.to_string())
+        .add_attribute("vote", vote)
+    )
+}
+
+fn execute_revoke_ballot(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: String,
+) -> Result<Response, ContractError> {
+    let mut poll = POLLS.may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {  })?;
+    ensure_poll_open(&poll, &env.block)?;
+
+    remove_ballot(deps.storage, &poll_id, &mut poll, &info.sender, env.block.height)?;
+    POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+    Ok(Response::new()
+        .add_attribute("action", "revoke_ballot")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("voter", info.sender.to_string())
+    )
+}
+
+// Descuenta el voto del votante y lo elimina. No guarda la encuesta
+fn remove_ballot(
+    storage: &mut dyn Storage,
+    poll_id: &str,
+    poll: &mut Poll,
+    voter: &Addr,
+    height: u64,
+) -> Result<Ballot, ContractError> {
+    let key = (voter.clone(), poll_id.to_string());
+    let ballot = ballots().may_load(storage, key.clone())?
+        .ok_or(ContractError::BallotNotFound {  })?;
+    accrue_conviction(storage, poll_id, poll, height)?;
+    tally_ballot(storage, poll_id, poll, &ballot.choice, ballot.weight, false)?;
+    ballots().remove(storage, key)?;
+    Ok(ballot)
+}
+
+fn execute_delete_poll(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: String,
+) -> Result<Response, ContractError> {
+    let poll = POLLS.may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {  })?;
+
+    // Solo el creador de la encuesta o el admin del contrato pueden eliminarla
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != poll.creator && !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {  });
+    }
+
+    // Eliminamos todos los votos asociados a la encuesta
+    let keys = ballots()
+        .idx
+        .poll
+        .prefix(poll_id.clone())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for key in keys.iter() {
+        ballots().remove(deps.storage, key.clone())?;
+    }
+
+    // Los tokens bloqueados se devuelven a cada votante
+    let stakes = STAKES
+        .prefix(&poll_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut refunds = vec![];
+    if let Some(token) = poll.weight_source.cw20_token() {
+        for (voter, amount) in stakes.iter() {
+            refunds.push(cw20_transfer(token, voter, *amount)?);
+        }
+    }
+    for (voter, _) in stakes.iter() {
+        STAKES.remove(deps.storage, (&poll_id, voter));
+    }
+
+    for option in poll.options.iter() {
+        SCORE_HISTOGRAMS.remove(deps.storage, (&poll_id, &option.option));
+    }
+    PAIRWISE.remove(deps.storage, &poll_id);
+    CONVICTION.remove(deps.storage, &poll_id);
+    CLOSING.remove(deps.storage, &poll_id);
+    let committers = COMMITMENTS
+        .prefix(&poll_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for voter in committers.iter() {
+        COMMITMENTS.remove(deps.storage, (&poll_id, voter));
+    }
+    let poll_delegations = POLL_DELEGATORS
+        .sub_prefix(&poll_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (delegate, delegator) in poll_delegations.iter() {
+        POLL_DELEGATIONS.remove(deps.storage, (delegator, &poll_id));
+        POLL_DELEGATORS.remove(deps.storage, (&poll_id, delegate, delegator));
+    }
+
+    // Los créditos de voz comprados no pasan a otra encuesta con el mismo id.
+    // Si la encuesta no se cerró, el pago sigue en el contrato y se devuelve
+    let buyers = VOICE_CREDITS
+        .prefix(&poll_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut credit_refunds = vec![];
+    for (voter, credits) in buyers.iter() {
+        VOICE_CREDITS.remove(deps.storage, (&poll_id, voter));
+        if let (Some(price), None) = (poll.method.credit_price(), &poll.result) {
+            let amount = price.amount * Uint128::from(*credits);
+            credit_refunds.push(BankMsg::Send {
+                to_address: voter.to_string(),
+                amount: vec![Coin { denom: price.denom.clone(), amount }],
+            });
+        }
+    }
+
+    POLLS.remove(deps.storage, poll_id.clone());
+    Ok(Response::new()
+        .add_messages(refunds)
+        .add_messages(credit_refunds)
+        .add_attribute("action", "delete_poll")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("deleted_by", info.sender.to_string())
+        .add_attribute("ballots_purged", keys.len().to_string())
+    )
+}
+
+fn execute_close_poll(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: String,
+) -> Result<Response, ContractError> {
+    let mut poll = POLLS.may_load(deps.storage, poll_id.clone())?
+        .ok_or(ContractError::PollNotFound {  })?;
+
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != poll.creator && !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {  });
+    }
+    if poll.result.is_some() {
+        return Err(ContractError::PollClosed {  });
+    }
+    let closing = match CLOSING.may_load(deps.storage, &poll_id)? {
+        Some(closing) => closing,
+        None => {
+            // Una encuesta secreta no se cierra durante los compromisos ni
+            // mientras llegan las revelaciones
+            if poll.reveal_end.is_some() && poll.status(&env.block) != PollStatus::Closed {
+                return Err(ContractError::RevealNotFinished {  });
+            }
+            // Con un fin fijado nadie puede acortar la votación; solo las
+            // encuestas abiertas indefinidamente se cierran a voluntad
+            if poll.end.is_some() && poll.status(&env.block) != PollStatus::Closed {
+                return Err(ContractError::PollNotClosed {  });
+            }
+
+            // La convicción deja de acumularse al cerrar
+            accrue_conviction(deps.storage, &poll_id, &poll, env.block.height)?;
+            // Desde aquí no se aceptan votos mientras se resuelven las delegaciones
+            if poll.end.is_none() {
+                poll.end = Some(Expiration::AtHeight(env.block.height));
+            }
+            Closing { height: env.block.height, cursor: DelegationCursor::Poll(None) }
+        }
+    };
+
+    // Cada votante suma el poder de quienes delegaron en él y no votaron. Si
+    // quedan delegaciones, el siguiente `ClosePoll` continúa desde el cursor
+    if let Some(cursor) = resolve_delegations(deps.branch(), &poll_id, &mut poll, closing.cursor)? {
+        CLOSING.save(deps.storage, &poll_id, &Closing { height: closing.height, cursor })?;
+        POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+        return Ok(Response::new()
+            .add_attribute("action", "close_poll")
+            .add_attribute("poll_id", poll_id)
+            .add_attribute("closed_by", info.sender.to_string())
+            .add_attribute("delegations_pending", "true")
+        );
+    }
+    CLOSING.remove(deps.storage, &poll_id);
+
+    // Congelamos el resultado; desde aquí no se aceptan más cambios
+    let mut result = poll.tally(&env.block);
+    if poll.method == VotingMethod::Ranked {
+        let rankings = ballots()
+            .idx
+            .poll
+            .prefix(poll_id.clone())
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                let (_, ballot) = item?;
+                let weight = ballot.weight + ballot.delegated;
+                Ok((ballot.choice.options().into_iter().cloned().collect(), weight))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        let options: Vec<String> = poll.options.iter().map(|o| o.option.clone()).collect();
+        let (winners, rounds) = tally::instant_runoff(&options, &rankings);
+        result.winners = winners;
+        result.rounds = Some(rounds);
+
+        // El resultado de Schulze sale de la matriz mantenida al votar
+        let preferences = load_pairwise(deps.storage, &poll_id, &poll)?;
+        let (_, ranking) = tally::schulze(&options, &preferences);
+        result.schulze = ranking.into_iter().next();
+    }
+    // Todas las opciones reciben una calificación por voto: el de mayor suma
+    // es también el de mayor promedio
+    if let VotingMethod::Score { .. } = poll.method {
+        let histograms = load_score_histograms(deps.storage, &poll_id, &poll)?;
+        let options: Vec<String> = poll.options.iter().map(|o| o.option.clone()).collect();
+        result.total_votes = histograms.first().map(|h| h.iter().sum()).unwrap_or_default();
+        result.majority_judgment = Some(tally::majority_judgment(&options, &histograms));
+    }
+    // Ganan las opciones cuya convicción alcanzó el umbral, no las más votadas
+    if let VotingMethod::Conviction { .. } = poll.method {
+        let state = load_conviction(deps.storage, &poll_id, &poll, closing.height)?;
+        result.winners = poll.options
+            .iter()
+            .zip(state.options.iter())
+            .filter(|(_, conviction)| conviction.passed_at.is_some())
+            .map(|(option, _)| option.option.clone())
+            .collect();
+    }
+    let winners = result.winners.join(",");
+    let total_votes = result.total_votes;
+    poll.result = Some(result);
+    POLLS.save(deps.storage, poll_id.clone(), &poll)?;
+
+    // El pago de los créditos de voz comprados pasa al creador
+    let mut payment = None;
+    if let Some(price) = poll.method.credit_price() {
+        let sold = VOICE_CREDITS
+            .prefix(&poll_id)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| Ok(Uint128::from(item?.1)))
+            .sum::<StdResult<Uint128>>()?;
+        if !sold.is_zero() {
+            payment = Some(BankMsg::Send {
+                to_address: poll.creator.to_string(),
+                amount: vec![Coin { denom: price.denom.clone(), amount: price.amount * sold }],
+            });
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(payment)
+        .add_attribute("action", "close_poll")
+        .add_attribute("poll_id", poll_id)
+        .add_attribute("closed_by", info.sender.to_string())
+        .add_attribute("winners", winners)
+        .add_attribute("total_votes", total_votes.to_string())
+    )
+}
+
+fn execute_delegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: String,
+    poll_id: Option<String>,
+) -> Result<Response, ContractError> {
+    let to = deps.api.addr_validate(&to)?;
+    if let Some(poll_id) = &poll_id {
+        let poll = POLLS.may_load(deps.storage, poll_id.clone())?
+            .ok_or(ContractError::PollNotFound {  })?;
+        if poll.status(&env.block) == PollStatus::Closed {
+            return Err(ContractError::PollClosed {  });
+        }
+    }
+
+    // Una delegación global rige en todas las encuestas sin delegación propia,
+    // así que la cadena desde `to` puede seguir por cualquiera de ellas
+    if poll_id.is_none() && reaches_in_any_poll(deps.storage, &env.block, &to, &info.sender)? {
+        return Err(ContractError::DelegationCycle {  });
+    }
+
+    // Recorremos la cadena desde `to`; si vuelve al remitente hay un ciclo. El
+    // largo se mide desde el delegador más lejano del remitente
+    let mut next = Some(to.clone());
+    let mut depth =
+        upstream_depth(deps.storage, poll_id.as_deref(), &info.sender, MAX_DELEGATION_DEPTH)?;
+    while let Some(delegate) = next {
+        if delegate == info.sender {
+            return Err(ContractError::DelegationCycle {  });
+        }
+        depth += 1;
+        if depth > MAX_DELEGATION_DEPTH {
+            return Err(ContractError::DelegationTooDeep {  });
+        }
+        next = match &poll_id {
+            Some(poll_id) => delegate_for(deps.storage, poll_id, &delegate)?,
+            None => DELEGATIONS.may_load(deps.storage, &delegate)?,
+        };
+    }
+
+    remove_delegation(deps.storage, &info.sender, poll_id.as_deref())?;
+    match &poll_id {
+        Some(poll_id) => {
+            POLL_DELEGATIONS.save(deps.storage, (&info.sender, poll_id), &to)?;
+            POLL_DELEGATORS.save(deps.storage, (poll_id, &to, &info.sender), &Empty {})?;
+        }
+        None => {
+            DELEGATIONS.save(deps.storage, &info.sender, &to)?;
+            DELEGATORS.save(deps.storage, (&to, &info.sender), &Empty {})?;
+        }
+    }
+    Ok(Response::new()
+        .add_attribute("action", "delegate")
+        .add_attribute("delegator", info.sender.to_string())
+        .add_attribute("delegate", to.to_string())
+        .add_attribute("poll_id", poll_id.unwrap_or_default())
+    )
+}
+
+fn execute_undelegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: Option<String>,
+) -> Result<Response, ContractError> {
+    // Al cerrar, las delegaciones de la encuesta se resuelven en varias tandas
+    // y no deben cambiar entre ellas
+    if let Some(poll_id) = &poll_id {
+        let poll = POLLS.may_load(deps.storage, poll_id.clone())?
+            .ok_or(ContractError::PollNotFound {  })?;
+        if poll.status(&env.block) == PollStatus::Closed {
+            return Err(ContractError::PollClosed {  });
+        }
+    }
+    remove_delegation(deps.storage, &info.sender, poll_id.as_deref())?
+        .ok_or(ContractError::DelegationNotFound {  })?;
+    Ok(Response::new()
+        .add_attribute("action", "undelegate")
+        .add_attribute("delegator", info.sender.to_string())
+        .add_attribute("poll_id", poll_id.unwrap_or_default())
+    )
+}
+
+// Elimina la delegación del alcance indicado y devuelve el delegado anterior
+fn remove_delegation(
+    storage: &mut dyn Storage,
+    delegator: &Addr,
+    poll_id: Option<&str>,
+) -> StdResult<Option<Addr>> {
+    let delegate = match poll_id {
+        Some(poll_id) => POLL_DELEGATIONS.may_load(storage, (delegator, poll_id))?,
+        None => DELEGATIONS.may_load(storage, delegator)?,
+    };
+    if let Some(delegate) = &delegate {
+        match poll_id {
+            Some(poll_id) => {
+                POLL_DELEGATIONS.remove(storage, (delegator, poll_id));
+                POLL_DELEGATORS.remove(storage, (poll_id, delegate, delegator));
+            }
+            None => {
+                DELEGATIONS.remove(storage, delegator);
+                DELEGATORS.remove(storage, (delegate, delegator));
+            }
+        }
+    }
+    Ok(delegate)
+}
+
+// Si `target` es alcanzable desde `from` en hasta `MAX_DELEGATION_DEPTH`
+// delegaciones, globales o de cualquier encuesta aún abierta. Más allá el poder
+// ya no se transfiere, y las encuestas cerradas no vuelven a usar sus delegaciones
+fn reaches_in_any_poll(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    from: &Addr,
+    target: &Addr,
+) -> StdResult<bool> {
+    let mut level = vec![from.clone()];
+    let mut visited = BTreeSet::new();
+    for _ in 0..=MAX_DELEGATION_DEPTH {
+        let mut next = vec![];
+        for address in level {
+            if address == *target {
+                return Ok(true);
+            }
+            if !visited.insert(address.clone()) {
+                continue;
+            }
+            next.extend(DELEGATIONS.may_load(storage, &address)?);
+            let poll_delegations = POLL_DELEGATIONS
+                .prefix(&address)
+                .range(storage, None, None, Order::Ascending);
+            for item in poll_delegations {
+                let (poll_id, delegate) = item?;
+                let poll = POLLS.load(storage, poll_id)?;
+                if poll.status(block) != PollStatus::Closed {
+                    next.push(delegate);
+                }
+            }
+        }
+        level = next;
+    }
+    Ok(false)
+}
+
+// Niveles de delegadores por encima de `delegate`, sin pasar de `limit` + 1
+fn upstream_depth(
+    storage: &dyn Storage,
+    poll_id: Option<&str>,
+    delegate: &Addr,
+    limit: u32,
+) -> StdResult<u32> {
+    let delegators = match poll_id {
+        Some(poll_id) => delegators_for(storage, poll_id, delegate)?,
+        None => DELEGATORS
+            .prefix(delegate)
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?,
+    };
+    let mut depth = 0;
+    for delegator in delegators {
+        if limit == 0 {
+            return Ok(1);
+        }
+        depth = depth.max(1 + upstream_depth(storage, poll_id, &delegator, limit - 1)?);
+    }
+    Ok(depth)
+}
+
+// Delegado de `delegator` en la encuesta: el de la encuesta o, si no hay, el global
+fn delegate_for(storage: &dyn Storage, poll_id: &str, delegator: &Addr) -> StdResult<Option<Addr>> {
+    match POLL_DELEGATIONS.may_load(storage, (delegator, poll_id))? {
+        Some(delegate) => Ok(Some(delegate)),
+        None => DELEGATIONS.may_load(storage, delegator),
+    }
+}
+
+// Quienes delegan en `delegate` para la encuesta
+fn delegators_for(storage: &dyn Storage, poll_id: &str, delegate: &Addr) -> StdResult<Vec<Addr>> {
+    let mut delegators = POLL_DELEGATORS
+        .prefix((poll_id, delegate))
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for delegator in DELEGATORS.prefix(delegate).keys(storage, None, None, Order::Ascending) {
+        let delegator = delegator?;
+        // Una delegación para esta encuesta reemplaza a la global
+        if !POLL_DELEGATIONS.has(storage, (&delegator, poll_id)) {
+            delegators.push(delegator);
+        }
+    }
+    Ok(delegators)
+}
+
+// Resuelve hasta `MAX_DELEGATIONS_PER_CLOSE` delegaciones desde `cursor`: el
+// poder de cada delegador que no votó pasa al primer votante de su cadena.
+// Devuelve dónde seguir, o `None` si ya no quedan delegaciones
+fn resolve_delegations(
+    deps: DepsMut,
+    poll_id: &str,
+    poll: &mut Poll,
+    mut cursor: DelegationCursor,
+) -> Result<Option<DelegationCursor>, ContractError> {
+    let mut delegators = vec![];
+    if let DelegationCursor::Poll(after) = &cursor {
+        let start = after
+            .as_ref()
+            .map(|(delegate, delegator)| Bound::exclusive((delegate, delegator)));
+        let keys = POLL_DELEGATORS
+            .sub_prefix(poll_id)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(MAX_DELEGATIONS_PER_CLOSE)
+            .collect::<StdResult<Vec<_>>>()?;
+        cursor = match keys.last() {
+            Some(last) if keys.len() == MAX_DELEGATIONS_PER_CLOSE => {
+                DelegationCursor::Poll(Some(last.clone()))
+            }
+            _ => DelegationCursor::Global(None),
+        };
+        delegators.extend(keys.into_iter().map(|(_, delegator)| delegator));
+    }
+    let mut pending = true;
+    if let DelegationCursor::Global(after) = &cursor {
+        let remaining = MAX_DELEGATIONS_PER_CLOSE - delegators.len();
+        let keys = DELEGATIONS
+            .keys(deps.storage, after.as_ref().map(Bound::exclusive), None, Order::Ascending)
+            .take(remaining)
+            .collect::<StdResult<Vec<_>>>()?;
+        pending = keys.len() == remaining;
+        cursor = DelegationCursor::Global(keys.last().cloned().or_else(|| after.clone()));
+        // Quien delegó para esta encuesta ya se resolvió con esas delegaciones
+        for delegator in keys {
+            if !POLL_DELEGATIONS.has(deps.storage, (&delegator, poll_id)) {
+                delegators.push(delegator);
+            }
+        }
+    }
+
+    let mut resolved = vec![];
+    for delegator in delegators {
+        if ballots().has(deps.storage, (delegator.clone(), poll_id.to_string())) {
+            continue;
+        }
+        if let Some(voter) = delegation_voter(deps.storage, poll_id, &delegator)? {
+            let power = own_power(deps.as_ref(), poll_id, poll, &delegator)?;
+            if !power.is_zero() {
+                resolved.push((voter, power));
+            }
+        }
+    }
+    for (voter, power) in resolved {
+        let key = (voter, poll_id.to_string());
+        let mut ballot = ballots().load(deps.storage, key.clone())?;
+        tally_ballot(deps.storage, poll_id, poll, &ballot.choice, power, true)?;
+        ballot.delegated += power;
+        ballots().save(deps.storage, key, &ballot)?;
+    }
+    Ok(pending.then_some(cursor))
+}
+
+// Primer votante en la cadena de delegaciones de `delegator` para la encuesta.
+// Más allá de `MAX_DELEGATION_DEPTH` el poder no se transfiere
+fn delegation_voter(
+    storage: &dyn Storage,
+    poll_id: &str,
+    delegator: &Addr,
+) -> StdResult<Option<Addr>> {
+    let mut next = delegate_for(storage, poll_id, delegator)?;
+    for _ in 0..MAX_DELEGATION_DEPTH {
+        let delegate = match next {
+            Some(delegate) => delegate,
+            None => return Ok(None),
+        };
+        if ballots().has(storage, (delegate.clone(), poll_id.to_string())) {
+            return Ok(Some(delegate));
+        }
+        next = delegate_for(storage, poll_id, &delegate)?;
+    }
+    Ok(None)
+}
+
+// Poder que recibe `delegate` de quienes delegan en él, directa o
+// indirectamente, sin haber votado en la encuesta. Es lo que sumará al cerrar
+// si nada cambia; como allí, no se pasa de `MAX_DELEGATION_DEPTH`
+fn delegated_power(
+    deps: Deps,
+    poll_id: &str,
+    poll: &Poll,
+    delegate: &Addr,
+    depth: u32,
+) -> Result<Uint128, ContractError> {
+    let mut power = Uint128::zero();
+    if depth >= MAX_DELEGATION_DEPTH {
+        return Ok(power);
+    }
+    for delegator in delegators_for(deps.storage, poll_id, delegate)? {
+        if ballots().has(deps.storage, (delegator.clone(), poll_id.to_string())) {
+            continue;
+        }
+        power += own_power(deps, poll_id, poll, &delegator)?;
+        power += delegated_power(deps, poll_id, poll, &delegator, depth + 1)?;
+    }
+    Ok(power)
+}
+
+// Como `voting_power`, pero sin poder de voto devuelve cero en lugar de error
+fn own_power(deps: Deps, poll_id: &str, poll: &Poll, voter: &Addr) -> Result<Uint128, ContractError> {
+    match voting_power(deps, poll_id, poll, voter) {
+        Err(ContractError::NoVotingPower {}) | Err(ContractError::NotEligible {}) => {
+            Ok(Uint128::zero())
+        }
+        power => power,
+    }
+}
+
+fn execute_propose_new_admin(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_admin: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {  });
+    }
+
+    // El traspaso queda pendiente hasta que el nuevo admin lo acepte
+    let new_admin = deps.api.addr_validate(&new_admin)?;
+    PENDING_ADMIN.save(deps.storage, &new_admin)?;
+    Ok(Response::new()
+        .add_attribute("action", "propose_new_admin")
+        .add_attribute("admin", info.sender.to_string())
+        .add_attribute("pending_admin", new_admin.to_string())
+    )
+}
+
+fn execute_accept_admin(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending_admin = PENDING_ADMIN.may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingAdmin {  })?;
+    if info.sender != pending_admin {
+        return Err(ContractError::Unauthorized {  });
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let previous_admin = config.admin
+        .replace(pending_admin.clone())
+        .map_or("none".to_string(), |admin| admin.to_string());
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_ADMIN.remove(deps.storage);
+    Ok(Response::new()
+        .add_attribute("action", "accept_admin")
+        .add_attribute("previous_admin", previous_admin)
+        .add_attribute("admin", pending_admin.to_string())
+    )
+}
+
+fn execute_renounce_admin(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {  });
+    }
+
+    // Sin admin nadie podría cobrar la tarifa de creación
+    if !config.creation_fee.is_empty() {
+        return Err(ContractError::CreationFeeWithoutAdmin {  });
+    }
+
+    // Sin admin, además se descarta cualquier traspaso pendiente
+    config.admin = None;
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_ADMIN.remove(deps.storage);
+    Ok(Response::new()
+        .add_attribute("action", "renounce_admin")
+        .add_attribute("previous_admin", info.sender.to_string())
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn execute_update_config(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_limits: Option<PollLimits>,
+    creation_fee: Option<Vec<Coin>>,
+    creation_policy: Option<CreationPolicy>,
+    add_creators: Option<Vec<String>>,
+    remove_creators: Option<Vec<String>>,
+    default_poll_duration: Option<Option<Duration>>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {  });
+    }
+
+    if let Some(poll_limits) = poll_limits {
+        validate_poll_limits(&poll_limits)?;
+        config.poll_limits = poll_limits;
+    }
+    if let Some(creation_fee) = creation_fee {
+        validate_creation_fee(&creation_fee)?;
+        config.creation_fee = creation_fee;
+    }
+    if let Some(creation_policy) = creation_policy {
+        config.creation_policy = creation_policy;
+    }
+    if let Some(default_poll_duration) = default_poll_duration {
+        if let Some(Duration::Height(0) | Duration::Time(0)) = default_poll_duration {
+            return Err(ContractError::InvalidPollDuration {  });
+        }
+        config.default_poll_duration = default_poll_duration;
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    for creator in add_creators.unwrap_or_default() {
+        let creator = deps.api.addr_validate(&creator)?;
+        ALLOWED_CREATORS.save(deps.storage, &creator, &Empty {})?;
+    }
+    for creator in remove_creators.unwrap_or_default() {
+        let creator = deps.api.addr_validate(&creator)?;
+        ALLOWED_CREATORS.remove(deps.storage, &creator);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_config")
+        .add_attribute("admin", info.sender.to_string())
+    )
+}
+
+// Cada moneda de la tarifa debe tener monto positivo y denominación única
+fn validate_creation_fee(fee: &[Coin]) -> Result<(), ContractError> {
+    for (i, coin) in fee.iter().enumerate() {
+        if coin.amount.is_zero() || fee[..i].iter().any(|c| c.denom == coin.denom) {
+            return Err(ContractError::InvalidCreationFee {  });
+        }
+    }
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::AllPolls { 
+            start_after, 
+            limit, 
+            order 
+        } => query_all_polls(deps, env, start_after, limit, order),
+        QueryMsg::Poll { poll_id  } => query_poll(deps, env, poll_id),
+        QueryMsg::Vote { address, poll_id } => query_vote(deps, env, address, poll_id),
+        QueryMsg::ConfigUser { } => query_config_user(deps, env),
+        QueryMsg::AllVoteUser { 
+            address, 
+            start_after, 
+            limit 
+        } => query_all_vote_user(deps, env, address, start_after, limit),
+        QueryMsg::Stake { poll_id, address } => query_stake(deps, env, poll_id, address),
+        QueryMsg::VoiceCredits { 
+            poll_id, 
+            address 
+        } => query_voice_credits(deps, env, poll_id, address),
+        QueryMsg::ScoreHistogram { poll_id } => query_score_histogram(deps, env, poll_id),
+        QueryMsg::Pairwise { poll_id } => query_pairwise(deps, env, poll_id),
+        QueryMsg::Conviction { poll_id } => query_conviction(deps, env, poll_id),
+        QueryMsg::VotingPower { 
+            poll_id, 
+            address 
+        } => query_voting_power(deps, env, poll_id, address),
+        QueryMsg::Commitments { 
+            poll_id, 
+            start_after, 
+            limit 
+        } => query_commitments(deps, env, poll_id, start_after, limit),
+        QueryMsg::Nonce { address } => query_nonce(deps, env, address),
+    }
+}
+
+fn query_all_polls(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    order: Option<OrderBy>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let order: Order = order.unwrap_or(OrderBy::Ascending).into();
+    let bound = start_after.map(Bound::exclusive);
+    // En orden descendente el cursor es el límite superior
+    let (min, max) = match order {
+        Order::Ascending => (bound, None),
+        Order::Descending => (None, bound),
+    };
+
+    let polls = POLLS
+        .range(deps.storage, min, max, order)
+        .take(limit)
+        .map(|p| {
+            let (poll_id, poll) = p?;
+            Ok(PollInfo { poll_id, poll })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&AllPollsResponse {polls})
+}
+
+fn query_poll(deps: Deps, env: Env, poll_id: String) -> StdResult<Binary> {
+    let poll = POLLS.may_load(deps.storage, poll_id)?;
+    let status = poll.as_ref().map(|poll| poll.status(&env.block));
+    // Una encuesta vencida sin cerrar se evalúa con los conteos actuales
+    let outcome = poll.as_ref().and_then(|poll| match &poll.result {
+        Some(result) => result.outcome,
+        None if status == Some(PollStatus::Closed) => poll.outcome(),
+        None => None,
+    });
+    to_binary(&PollResponse {poll, status, outcome})
+}
+
+fn query_vote(deps: Deps, _env: Env, address: String, poll_id: String) -> StdResult<Binary> {
+    let validated_address = deps.api.addr_validate(&address).unwrap();
+    let vote = ballots().may_load(deps.storage, (validated_address, poll_id))?;
+
+    to_binary(&VoteResponse { vote})
+}
+
+fn query_config_user(deps: Deps, _env: Env) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let pending_admin = PENDING_ADMIN.may_load(deps.storage)?;
+    to_binary(&UserAdminResponse {
+        user_admin: config.admin.map(|admin| admin.to_string()),
+        pending_admin: pending_admin.map(|admin| admin.to_string()),
+        poll_limits: config.poll_limits,
+        creation_fee: config.creation_fee,
+        creation_policy: config.creation_policy,
+        default_poll_duration: config.default_poll_duration
+    })
+}
+
+fn query_all_vote_user(
+    deps: Deps,
+    _env: Env,
+    address: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let validated_address = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    // Recorremos los votos por el prefijo del votante
+    let votes = ballots()
+        .prefix(validated_address)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (poll_id, ballot) = item?;
+            Ok(UserVote { poll_id, ballot })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&AllVoteUserResponse { votes })
+}
+
+fn query_stake(deps: Deps, _env: Env, poll_id: String, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let amount = STAKES.may_load(deps.storage, (&poll_id, &address))?.unwrap_or_default();
+    to_binary(&StakeResponse { amount })
+}
+
+fn query_nonce(deps: Deps, _env: Env, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let nonce = NONCES.may_load(deps.storage, &address)?.unwrap_or_default();
+    to_binary(&NonceResponse { nonce })
+}
+
+fn query_voice_credits(
+    deps: Deps,
+    _env: Env,
+    poll_id: String,
+    address: String,
+) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let poll = POLLS.load(deps.storage, poll_id.clone())?;
+    let budget = match poll.method {
+        VotingMethod::Quadratic { credits: VoiceCredits::Fixed { amount } } => amount,
+        VotingMethod::Quadratic { credits: VoiceCredits::Purchased { .. } } => {
+            VOICE_CREDITS.may_load(deps.storage, (&poll_id, &address))?.unwrap_or_default()
+        }
+        _ => 0,
+    };
+    let spent = match ballots().may_load(deps.storage, (address, poll_id))? {
+        Some(ballot) => quadratic_cost(&ballot.choice)?,
+        None => Uint128::zero(),
+    };
+    to_binary(&VoiceCreditsResponse { budget: budget.into(), spent })
+}
+
+fn query_score_histogram(deps: Deps, _env: Env, poll_id: String) -> StdResult<Binary> {
+    let poll = POLLS.load(deps.storage, poll_id.clone())?;
+    let histograms = load_score_histograms(deps.storage, &poll_id, &poll)?;
+    let options = poll.options
+        .into_iter()
+        .zip(histograms)
+        .map(|(option, histogram)| OptionHistogram {
+            option: option.option,
+            sum: option.votes,
+            median: tally::median_grade(&histogram),
+            histogram,
+        })
+        .collect();
+    to_binary(&ScoreHistogramResponse { options })
+}
+
+fn query_conviction(deps: Deps, env: Env, poll_id: String) -> StdResult<Binary> {
+    let poll = POLLS.load(deps.storage, poll_id.clone())?;
+    let threshold = match &poll.method {
+        VotingMethod::Conviction { threshold, .. } => *threshold,
+        _ => return Err(StdError::generic_err("Poll doesn't use conviction voting")),
+    };
+    // Una encuesta cerrada conserva la convicción del cierre
+    let height = poll.result
+        .as_ref()
+        .map_or(env.block.height, |result| result.closed_at_height);
+    let state = load_conviction(deps.storage, &poll_id, &poll, height)?;
+    let options = poll.options
+        .into_iter()
+        .zip(state.options)
+        .map(|(option, conviction)| OptionConvictionInfo {
+            option: option.option,
+            staked: option.votes,
+            conviction: conviction.conviction,
+            passed_at: conviction.passed_at,
+        })
+        .collect();
+    to_binary(&ConvictionResponse { height, threshold, options })
+}
+
+fn query_pairwise(deps: Deps, _env: Env, poll_id: String) -> StdResult<Binary> {
+    let poll = POLLS.load(deps.storage, poll_id.clone())?;
+    let options: Vec<String> = poll.options.iter().map(|o| o.option.clone()).collect();
+    let preferences = load_pairwise(deps.storage, &poll_id, &poll)?;
+    let (strongest_paths, ranking) = tally::schulze(&options, &preferences);
+    to_binary(&PairwiseResponse { options, preferences, strongest_paths, ranking })
+}
+
+fn query_voting_power(
+    deps: Deps,
+    _env: Env,
+    poll_id: String,
+    address: String,
+) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let poll = POLLS.load(deps.storage, poll_id.clone())?;
+    let to_std = |err: ContractError| StdError::generic_err(err.to_string());
+    let own = own_power(deps, &poll_id, &poll, &address).map_err(to_std)?;
+    let delegated = delegated_power(deps, &poll_id, &poll, &address, 0).map_err(to_std)?;
+    let delegate = delegate_for(deps.storage, &poll_id, &address)?.map(|d| d.to_string());
+    let voted = ballots().has(deps.storage, (address, poll_id));
+    to_binary(&VotingPowerResponse { own, delegated, delegate, voted })
+}
+
+fn query_commitments(
+    deps: Deps,
+    _env: Env,
+    poll_id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(|address| deps.api.addr_validate(&address)).transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let commitments = COMMITMENTS
+        .prefix(&poll_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (voter, commitment) = item?;
+            Ok(Commitment { voter, commitment })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&CommitmentsResponse { commitments })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+    use std::vec;
+
+    use cosmwasm_std::{
+        attr, coin, coins, from_binary, to_binary, to_vec, Addr, Api, Binary, CanonicalAddr, Coin, Decimal,
+        BankMsg, ContractResult, CosmosMsg, Empty, FullDelegation, OwnedDeps, RecoverPubkeyError,
+        StdResult, SystemResult, Uint128, Validator, VerificationError, WasmMsg, WasmQuery
+    };
+    use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+    use cw4::{Cw4QueryMsg, MemberResponse};
+    use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+    use ripemd::Ripemd160;
+    use sha2::{Digest, Sha256};
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_dependencies_with_balances, mock_env, mock_info, MockApi,
+        MockQuerier, MockStorage
+    };
+    use cw2::{get_contract_version, set_contract_version};
+    use cw_utils::{Duration, Expiration, Scheduled};
+    use crate::contract::{
+        instantiate, execute, migrate, commitment_hash, CONTRACT_NAME, CONTRACT_VERSION,
+        MAX_DELEGATIONS_PER_CLOSE
+    };
+    use crate::migrations::v0_1;
+    use crate::ContractError;
+    use crate::msg::{
+        InstantiateMsg, ExecuteMsg, AllPollsResponse, PollResponse, QueryMsg,
+        VoteResponse, UserAdminResponse, AllVoteUserResponse, MigrateMsg, OrderBy, ReceiveMsg,
+        StakeResponse, VoiceCreditsResponse, ScoreHistogramResponse, PairwiseResponse,
+        VotingPowerResponse, CommitmentsResponse, NonceResponse, SignedVote, VotePayload,
+        ConvictionResponse
+    };
+    use crate::state::{
+        Choice, CreationPolicy, IrvRound, OptionScore, PollLimits, PollOption, PollOutcome,
+        PollRules, PollStatus, QuadraticVote, Quorum, Threshold, Veto, VoiceCredits,
+        VotingMethod, WeightSource
+    };
+
+    use super::query;
+
+    pub const ADDR1: &str = "addr1";
+    pub const ADDR2: &str = "addr2";
+    #[test]
+    fn test_instantiate(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        
+        // Create a message where we (the sender) will be an admin
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+        assert_eq!(
+            res.attributes,
+            vec![attr("action", "instantiate"), attr("admin", ADDR1)]
+        )
+    }
+
+    #[test]
+    fn test_instantiate_with_admin(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        
+        // Create a message where admin is ADDR2
+        let msg = InstantiateMsg { admin: Some(ADDR2.to_string()), poll_limits: None };
+        let res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+        assert_eq!(
+            res.attributes,
+            vec![attr("action", "instantiate"), attr("admin", ADDR2)]
+        )
+    }
+
+    #[test]
+    fn test_execute_create_poll_valid(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instanciamos el contrato
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // msg de nueva ejecución CreatePoll
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favourite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+
+        // Unwrap para el assert
+        let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+    }
+
+    #[test]
+    fn test_execute_create_poll_invalid(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instanciamos el contrato
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favorite number?".to_string(), 
+            options: vec![
+                "1".to_string(),
+                "2".to_string(),
+                "3".to_string(),
+                "4".to_string(),
+                "5".to_string(),
+                "6".to_string(),
+                "7".to_string(),
+                "8".to_string(),
+                "9".to_string(),
+                "10".to_string(),
+                "11".to_string(),
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+
+        // Unwrap error para afirmar una falla
+        let _err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    }
+
+    #[test]
+    fn test_execute_create_poll_content_rules(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Límites personalizados: entre 2 y 3 opciones, textos cortos
+        let msg = InstantiateMsg { 
+            admin: None, 
+            poll_limits: Some(PollLimits {
+                min_options: 2,
+                max_options: 3,
+                max_question_length: 20,
+                max_option_length: 5,
+                unique_options: true
+            }) 
+        };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let cases = vec![
+            ("", vec!["Juno", "Atom"]),
+            ("   ", vec!["Juno", "Atom"]),
+            ("Which is the best Cosmos coin?", vec!["Juno", "Atom"]),
+            ("Favourite coin?", vec!["Juno"]),
+            ("Favourite coin?", vec!["Juno", "Atom", "Osmo", "Scrt"]),
+            ("Favourite coin?", vec!["Juno", ""]),
+            ("Favourite coin?", vec!["Juno", "Osmosis"]),
+            ("Favourite coin?", vec!["Juno", "Atom", "Juno"]),
+        ];
+        let mut errors = vec![];
+        for (question, options) in cases {
+            let msg = ExecuteMsg::CreatePoll { 
+                poll_id: None, 
+                question: question.to_string(), 
+                options: options.iter().map(|o| o.to_string()).collect(),
+                start: None,
+                end: None,
+                method: None,
+                weight_source: None,
+                rules: None,
+                reveal_end: None
+            };
+            errors.push(execute(
+                deps.as_mut(), 
+                env.clone(), 
+                info.clone(), 
+                msg
+            ).unwrap_err());
+        }
+        assert!(matches!(errors[0], ContractError::EmptyQuestion {}));
+        assert!(matches!(errors[1], ContractError::EmptyQuestion {}));
+        assert!(matches!(errors[2], ContractError::QuestionTooLong {}));
+        assert!(matches!(errors[3], ContractError::TooFewOptions {}));
+        assert!(matches!(errors[4], ContractError::TooManyOptions {}));
+        assert!(matches!(errors[5], ContractError::EmptyOption {}));
+        assert!(matches!(errors[6], ContractError::OptionTooLong { .. }));
+        assert!(matches!(errors[7], ContractError::DuplicateOption { .. }));
+
+        // Una encuesta que cumple los límites
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: None, 
+            question: "Favourite coin?".to_string(), 
+            options: vec![
+                "Juno".to_string(),
+                "Atom".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+    }
+
+    #[test]
+    fn test_instantiate_invalid_poll_limits(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Mínimo mayor que el máximo
+        let msg = InstantiateMsg { 
+            admin: None, 
+            poll_limits: Some(PollLimits {
+                min_options: 5,
+                max_options: 3,
+                ..PollLimits::default()
+            }) 
+        };
+        let err = instantiate(deps.as_mut(), env, info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPollLimits {}));
+    }
+
+    #[test]
+    fn test_execute_create_poll_already_exists(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favourite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info, 
+            msg.clone()
+        ).unwrap();
+
+        // Otro usuario intenta reemplazar la encuesta con el mismo id
+        let err = execute(
+            deps.as_mut(), 
+            env, 
+            mock_info(ADDR2, &[]), 
+            msg
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::PollAlreadyExists {}));
+    }
+
+    #[test]
+    fn test_execute_create_poll_assigned_id(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Sin poll_id el contrato asigna "1"
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: None, 
+            question: "What's your favourite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg.clone()
+        ).unwrap();
+        assert_eq!(res.attributes[1], attr("poll_id", "1"));
+
+        // Un usuario elige manualmente el id "2"
+        let msg_manual = ExecuteMsg::CreatePoll { 
+            poll_id: Some("2".to_string()), 
+            question: "What's other coin do you use?".to_string(), 
+            options: vec![
+                "Near".to_string(),
+                "Bitcoin".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg_manual
+        ).unwrap();
+
+        // El siguiente id asignado salta el "2" ocupado
+        let res = execute(
+            deps.as_mut(), 
+            env, 
+            info, 
+            msg
+        ).unwrap();
+        assert_eq!(res.attributes[1], attr("poll_id", "3"));
+    }
+
+    #[test]
+    fn test_execute_vote_valid(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instanciando el contrato
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Creacion de encuesta
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favorite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Creación del voto, primera votación en la encuesta
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string() 
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // cambio en el voto
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Osmosis".to_string()
+        };
+        let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    }
+
+    #[test]
+    fn test_execute_vote_invalid(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instanciación del contrato
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Creación de voto, con some_id de una encuesta no creado aun
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string() };
+        // Unwrap para afirmar el error
+        let _err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap_err();
+
+        // Creación de encuesta
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favourite cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Voto en encuesta existente pero con opción "DVPN" inexistente
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "DVPN".to_string() 
+        };
+        let _err = execute(
+            deps.as_mut(), 
+            env, 
+            info, 
+            msg
+        ).unwrap_err();
+    }
+
+    #[test]
+    fn test_execute_vote_poll_window(){
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // La encuesta abre en 10 bloques y cierra en 20
+        let height = env.block.height;
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favourite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string()
+            ],
+            start: Some(Scheduled::AtHeight(height + 10)),
+            end: Some(Expiration::AtHeight(height + 20)),
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let vote = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string() 
+        };
+        let status = QueryMsg::Poll { poll_id: "some_id".to_string() };
+
+        // Antes del inicio
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            vote.clone()
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::PollNotStarted {}));
+        let bin = query(deps.as_ref(), env.clone(), status.clone()).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.status, Some(PollStatus::Pending));
+
+        // Durante la votación
+        env.block.height = height + 10;
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            vote.clone()
+        ).unwrap();
+        let bin = query(deps.as_ref(), env.clone(), status.clone()).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.status, Some(PollStatus::Open));
+
+        // El creador no puede adelantar el fin de la votación
+        let close = ExecuteMsg::ClosePoll { poll_id: "some_id".to_string() };
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            close.clone()
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::PollNotClosed {}));
+
+        // Después del cierre
+        env.block.height = height + 20;
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            vote
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::PollClosed {}));
+        let bin = query(deps.as_ref(), env.clone(), status).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.status, Some(PollStatus::Closed));
+        let _res = execute(deps.as_mut(), env, info, close).unwrap();
+    }
+
+    #[test]
+    fn test_execute_create_poll_invalid_window(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Fin en el pasado
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favourite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string()
+            ],
+            start: None,
+            end: Some(Expiration::AtHeight(env.block.height)),
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPollWindow {}));
+
+        // Inicio posterior al fin
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favourite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string()
+            ],
+            start: Some(Scheduled::AtTime(env.block.time.plus_seconds(200))),
+            end: Some(Expiration::AtTime(env.block.time.plus_seconds(100))),
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let err = execute(
+            deps.as_mut(), 
+            env, 
+            info, 
+            msg
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPollWindow {}));
+    }
+
+    #[test]
+    fn test_execute_vote_approval(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Método de aprobación con máximo 2 opciones por votante
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "Which chains do you use?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string()
+            ],
+            start: None,
+            end: None,
+            method: Some(VotingMethod::Approval { max_selections: Some(2) }),
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let approve = |options: &[&str]| ExecuteMsg::CastBallot { 
+            poll_id: "some_id".to_string(), 
+            choice: Choice::Approval(options.iter().map(|o| o.to_string()).collect()) 
+        };
+        let votes = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> Vec<u128> {
+            let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+            let bin = query(deps.as_ref(), mock_env(), msg).unwrap();
+            let res: PollResponse = from_binary(&bin).unwrap();
+            res.poll.unwrap().options.iter().map(|o| o.votes.u128()).collect()
+        };
+
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            approve(&["Juno", "Osmosis"])
+        ).unwrap();
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            approve(&["Juno"])
+        ).unwrap();
+        assert_eq!(votes(&deps), vec![0, 2, 1]);
+
+        // Al volver a votar se descuentan todas las selecciones anteriores
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            approve(&["Cosmos Hub"])
+        ).unwrap();
+        assert_eq!(votes(&deps), vec![1, 1, 0]);
+
+        // Votos inválidos
+        let invalid_votes = vec![
+            approve(&[]),
+            approve(&["Cosmos Hub", "Juno", "Osmosis"]),
+            approve(&["Juno", "Juno"]),
+            approve(&["Juno", "DVPN"]),
+            ExecuteMsg::Vote { poll_id: "some_id".to_string(), vote: "Juno".to_string() },
+        ];
+        let errors: Vec<_> = invalid_votes
+            .into_iter()
+            .map(|msg| execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err())
+            .collect();
+        assert!(matches!(errors[0], ContractError::EmptyBallot {}));
+        assert!(matches!(errors[1], ContractError::TooManySelections {}));
+        assert!(matches!(errors[2], ContractError::DuplicateSelection { .. }));
+        assert!(matches!(errors[3], ContractError::OptionNotFound {}));
+        assert!(matches!(errors[4], ContractError::InvalidBallotType {}));
+        assert_eq!(votes(&deps), vec![1, 1, 0]);
+
+        // Retiro del voto completo
+        let msg = ExecuteMsg::RevokeBallot { poll_id: "some_id".to_string() };
+        let _res = execute(
+            deps.as_mut(), 
+            env, 
+            info, 
+            msg
+        ).unwrap();
+        assert_eq!(votes(&deps), vec![0, 1, 0]);
+    }
+
+    #[test]
+    fn test_execute_vote_native_balance_weight(){
+        let mut deps = mock_dependencies_with_balances(&[
+            (ADDR1, &coins(100, "ujuno")),
+            (ADDR2, &coins(30, "ujuno"))
+        ]);
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favorite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: Some(WeightSource::NativeBalance { denom: "ujuno".to_string() }),
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let vote = |option: &str| ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: option.to_string()
+        };
+        let votes = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> Vec<u128> {
+            let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+            let bin = query(deps.as_ref(), mock_env(), msg).unwrap();
+            let res: PollResponse = from_binary(&bin).unwrap();
+            res.poll.unwrap().options.iter().map(|o| o.votes.u128()).collect()
+        };
+
+        // Cada voto pesa el saldo del votante en ujuno
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), vote("Juno")).unwrap();
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            vote("Osmosis")
+        ).unwrap();
+        assert_eq!(votes(&deps), vec![0, 100, 30]);
+
+        // El saldo cambia: al votar de nuevo se descuenta el peso guardado y
+        // se suma el nuevo
+        deps.querier.update_balance(ADDR1, coins(40, "ujuno"));
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), vote("Osmosis")).unwrap();
+        assert_eq!(votes(&deps), vec![0, 0, 70]);
+
+        let msg = QueryMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            address: ADDR1.to_string()
+        };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: VoteResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.vote.unwrap().weight, Uint128::new(40));
+
+        // Revocar descuenta el peso guardado aunque el saldo haya cambiado
+        deps.querier.update_balance(ADDR2, coins(500, "ujuno"));
+        let msg = ExecuteMsg::RevokeBallot { poll_id: "some_id".to_string() };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap();
+        assert_eq!(votes(&deps), vec![0, 0, 40]);
+
+        // Sin saldo no hay poder de voto
+        let err = execute(deps.as_mut(), env, mock_info("addr3", &[]), vote("Juno")).unwrap_err();
+        assert!(matches!(err, ContractError::NoVotingPower {}));
+    }
+
+    #[test]
+    fn test_execute_vote_staked_weight(){
+        let mut deps = mock_dependencies();
+        let delegation = |delegator: &str, validator: &str, amount: u128| FullDelegation { 
+            delegator: Addr::unchecked(delegator), 
+            validator: validator.to_string(), 
+            amount: coin(amount, "ustake"), 
+            can_redelegate: coin(0, "ustake"), 
+            accumulated_rewards: vec![]
+        };
+        let validator = |address: &str| Validator { 
+            address: address.to_string(), 
+            commission: Decimal::percent(5), 
+            max_commission: Decimal::percent(20), 
+            max_change_rate: Decimal::percent(1)
+        };
+        deps.querier.update_staking(
+            "ustake",
+            &[validator("val1"), validator("val2")],
+            &[
+                delegation(ADDR1, "val1", 100),
+                delegation(ADDR1, "val2", 50),
+                delegation(ADDR2, "val2", 30)
+            ],
+        );
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let create = |poll_id: &str, validators: Option<Vec<String>>| ExecuteMsg::CreatePoll { 
+            poll_id: Some(poll_id.to_string()), 
+            question: "What's your favorite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: Some(WeightSource::Staked { validators }),
+            rules: None,
+            reveal_end: None
+        };
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            create("some_id", Some(vec!["val1".to_string(), "val1".to_string()]))
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidValidatorSet {}));
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), create("some_id", None)).unwrap();
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            create("val1_only", Some(vec!["val1".to_string()]))
+        ).unwrap();
+
+        let vote = |poll_id: &str, option: &str| ExecuteMsg::Vote { 
+            poll_id: poll_id.to_string(), 
+            vote: option.to_string()
+        };
+        let votes = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, poll_id: &str| -> Vec<u128> {
+            let msg = QueryMsg::Poll { poll_id: poll_id.to_string() };
+            let bin = query(deps.as_ref(), mock_env(), msg).unwrap();
+            let res: PollResponse = from_binary(&bin).unwrap();
+            res.poll.unwrap().options.iter().map(|o| o.votes.u128()).collect()
+        };
+
+        // Cada voto pesa el stake delegado, a todos los validadores o solo al conjunto
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), vote("some_id", "Juno")).unwrap();
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            vote("some_id", "Osmosis")
+        ).unwrap();
+        assert_eq!(votes(&deps, "some_id"), vec![0, 150, 30]);
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), vote("val1_only", "Juno")).unwrap();
+        assert_eq!(votes(&deps, "val1_only"), vec![0, 100, 0]);
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            vote("val1_only", "Juno")
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::NoVotingPower {}));
+
+        // Tras redelegar, revotar descuenta el peso guardado y suma el nuevo
+        deps.querier.update_staking(
+            "ustake",
+            &[validator("val1"), validator("val2")],
+            &[delegation(ADDR1, "val1", 20), delegation(ADDR2, "val2", 30)],
+        );
+        let _res = execute(deps.as_mut(), env.clone(), info, vote("some_id", "Osmosis")).unwrap();
+        assert_eq!(votes(&deps, "some_id"), vec![0, 0, 50]);
+        let msg = ExecuteMsg::RevokeBallot { poll_id: "some_id".to_string() };
+        let _res = execute(deps.as_mut(), env, mock_info(ADDR2, &[]), msg).unwrap();
+        assert_eq!(votes(&deps, "some_id"), vec![0, 0, 20]);
+    }
+
+    #[test]
+    fn test_execute_vote_cw20_balance_weight(){
+        let mut deps = mock_dependencies();
+        // El contrato cw20 responde con 250 tokens para addr1 y nada para el resto
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "token" => {
+                let balance = match from_binary(msg).unwrap() {
+                    Cw20QueryMsg::Balance { address } if address == ADDR1 => Uint128::new(250),
+                    _ => Uint128::zero(),
+                };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&BalanceResponse { balance }).unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favorite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: Some(WeightSource::Cw20Balance { token: "token".to_string() }),
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string()
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), env, mock_info(ADDR2, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NoVotingPower {}));
+
+        let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+        let bin = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.poll.unwrap().options[1].votes, Uint128::new(250));
+    }
+
+    #[test]
+    fn test_execute_vote_cw4_group(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let creation_height = env.block.height;
+        // addr1 pesa 5 en el grupo a la altura de creación; después pesa 9
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "group" => {
+                let weight = match from_binary(msg).unwrap() {
+                    Cw4QueryMsg::Member { addr, at_height } if addr == ADDR1 => {
+                        if at_height == Some(creation_height) { Some(5) } else { Some(9) }
+                    }
+                    Cw4QueryMsg::Member { addr, .. } if addr == "addr3" => Some(0),
+                    _ => None,
+                };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&MemberResponse { weight }).unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favorite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: Some(WeightSource::Cw4Group { group: "group".to_string() }),
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Se vota en un bloque posterior, pero cuenta el peso de la creación
+        let mut later = env.clone();
+        later.block.height += 10;
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string()
+        };
+        let _res = execute(deps.as_mut(), later.clone(), info, msg.clone()).unwrap();
+
+        let query_msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+        let bin = query(deps.as_ref(), later.clone(), query_msg).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        let poll = res.poll.unwrap();
+        assert_eq!(poll.snapshot_height, Some(creation_height));
+        assert_eq!(poll.options[1].votes, Uint128::new(5));
+
+        // Quien no es miembro no puede votar; un miembro con peso cero tampoco
+        let err = execute(
+            deps.as_mut(), 
+            later.clone(), 
+            mock_info(ADDR2, &[]), 
+            msg.clone()
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::NotEligible {}));
+        let err = execute(deps.as_mut(), later, mock_info("addr3", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NoVotingPower {}));
+    }
+
+    #[test]
+    fn test_execute_receive_cw20_stake(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favorite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: Some(WeightSource::Cw20Staked { token: "token".to_string() }),
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let receive = |amount: u128, option: &str| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: ADDR2.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_binary(&ReceiveMsg::Vote { 
+                poll_id: "some_id".to_string(), 
+                choice: Choice::Single(option.to_string())
+            }).unwrap(),
+        });
+        let votes = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> Vec<u128> {
+            let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+            let bin = query(deps.as_ref(), mock_env(), msg).unwrap();
+            let res: PollResponse = from_binary(&bin).unwrap();
+            res.poll.unwrap().options.iter().map(|o| o.votes.u128()).collect()
+        };
+
+        // Solo se aceptan tokens del contrato configurado
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info("other_token", &[]), 
+            receive(50, "Juno")
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidToken {}));
+
+        // Un segundo depósito se suma al bloqueado y puede cambiar la elección
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info("token", &[]), 
+            receive(50, "Juno")
+        ).unwrap();
+        assert_eq!(votes(&deps), vec![0, 50]);
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info("token", &[]), 
+            receive(30, "Cosmos Hub")
+        ).unwrap();
+        assert_eq!(votes(&deps), vec![80, 0]);
+
+        // Cambiar la elección sin depositar usa el monto bloqueado
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string()
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap();
+        assert_eq!(votes(&deps), vec![0, 80]);
+
+        // Los tokens no se retiran mientras la encuesta está abierta
+        let msg = ExecuteMsg::Withdraw { poll_id: "some_id".to_string() };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::PollNotClosed {}));
+
+        let close = ExecuteMsg::ClosePoll { poll_id: "some_id".to_string() };
+        let _res = execute(deps.as_mut(), env.clone(), info, close).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg.clone()).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "token".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer { 
+                    recipient: ADDR2.to_string(), 
+                    amount: Uint128::new(80)
+                }).unwrap(),
+                funds: vec![]
+            })
+        );
+
+        let query_msg = QueryMsg::Stake { 
+            poll_id: "some_id".to_string(), 
+            address: ADDR2.to_string()
+        };
+        let bin = query(deps.as_ref(), env.clone(), query_msg).unwrap();
+        let res: StakeResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.amount, Uint128::zero());
+        let err = execute(deps.as_mut(), env, mock_info(ADDR2, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NothingToWithdraw {}));
+    }
+
+    #[test]
+    fn test_execute_vote_quadratic(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let create = |poll_id: &str, credits: VoiceCredits| ExecuteMsg::CreatePoll { 
+            poll_id: Some(poll_id.to_string()), 
+            question: "What's your favorite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string()
+            ],
+            start: None,
+            end: None,
+            method: Some(VotingMethod::Quadratic { credits }),
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let ballot = |poll_id: &str, votes: &[(&str, u64)]| ExecuteMsg::CastBallot { 
+            poll_id: poll_id.to_string(), 
+            choice: Choice::Quadratic(
+                votes
+                    .iter()
+                    .map(|(option, votes)| QuadraticVote { option: option.to_string(), votes: *votes })
+                    .collect()
+            )
+        };
+        let votes = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, poll_id: &str| -> Vec<u128> {
+            let msg = QueryMsg::Poll { poll_id: poll_id.to_string() };
+            let bin = query(deps.as_ref(), mock_env(), msg).unwrap();
+            let res: PollResponse = from_binary(&bin).unwrap();
+            res.poll.unwrap().options.iter().map(|o| o.votes.u128()).collect()
+        };
+
+        // Presupuesto fijo de 10 créditos: 3 votos cuestan 9 y 1 voto cuesta 1
+        let msg = create("fixed", VoiceCredits::Fixed { amount: 10 });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ballot("fixed", &[("Cosmos Hub", 3), ("Juno", 2)]);
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientCredits { .. }));
+        let msg = ballot("fixed", &[("Cosmos Hub", 3), ("Juno", 1)]);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap();
+        assert_eq!(votes(&deps, "fixed"), vec![3, 1]);
+
+        let msg = ballot("fixed", &[("Cosmos Hub", 0)]);
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidVoteCount { .. }));
+
+        // Créditos comprados a 100ujuno cada uno; el contrato guarda los fondos
+        // hasta el cierre
+        let msg = create("purchased", VoiceCredits::Purchased { price: coin(100, "ujuno") });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ballot("purchased", &[("Juno", 2)]);
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &coins(150, "ujuno")), 
+            msg.clone()
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidCreditPurchase {}));
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[coin(400, "ujuno"), coin(1, "uatom")]), 
+            msg.clone()
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::UnexpectedFunds {}));
+        let res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &coins(400, "ujuno")), 
+            msg
+        ).unwrap();
+        assert!(res.messages.is_empty());
+
+        // Los créditos comprados se conservan al cambiar el voto
+        let msg = ballot("purchased", &[("Juno", 2), ("Cosmos Hub", 1)]);
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientCredits { .. }));
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &coins(100, "ujuno")), 
+            msg
+        ).unwrap();
+        assert_eq!(votes(&deps, "purchased"), vec![1, 2]);
+
+        let msg = QueryMsg::VoiceCredits { 
+            poll_id: "purchased".to_string(), 
+            address: ADDR2.to_string()
+        };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: VoiceCreditsResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.budget, Uint128::new(5));
+        assert_eq!(res.spent, Uint128::new(5));
+
+        // Eliminar una encuesta abierta devuelve los créditos comprados
+        let msg = create("refunded", VoiceCredits::Purchased { price: coin(100, "ujuno") });
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ballot("refunded", &[("Juno", 2)]);
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &coins(400, "ujuno")), 
+            msg
+        ).unwrap();
+        let msg = ExecuteMsg::DeletePoll { poll_id: "refunded".to_string() };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: ADDR2.to_string(),
+                amount: coins(400, "ujuno")
+            })
+        );
+
+        // Al cerrar, el creador recibe el pago de todos los créditos vendidos
+        let msg = ExecuteMsg::ClosePoll { poll_id: "purchased".to_string() };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: ADDR1.to_string(),
+                amount: coins(500, "ujuno")
+            })
+        );
+    }
+
+    #[test]
+    fn test_execute_vote_conviction(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let create = |decay: Decimal, weight_source: WeightSource| ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "Which project should the community pool fund?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string()
+            ],
+            start: None,
+            end: None,
+            method: Some(VotingMethod::Conviction { decay, threshold: Uint128::new(170) }),
+            weight_source: Some(weight_source),
+            rules: None,
+            reveal_end: None
+        };
+        let staked = WeightSource::Cw20Staked { token: "token".to_string() };
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            create(Decimal::one(), staked.clone())
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidVotingMethod {}));
+        // Con saldos libres los mismos tokens respaldarían una opción desde
+        // varias direcciones
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            create(Decimal::percent(50), WeightSource::NativeBalance { denom: "ujuno".to_string() })
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidVotingMethod {}));
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            create(Decimal::percent(50), staked)
+        ).unwrap();
+
+        let stake = |voter: &str, amount: u128, option: &str| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: voter.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_binary(&ReceiveMsg::Vote { 
+                poll_id: "some_id".to_string(), 
+                choice: Choice::Single(option.to_string())
+            }).unwrap(),
+        });
+        let vote = |option: &str| ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: option.to_string()
+        };
+        let at = |blocks: u64| -> cosmwasm_std::Env {
+            let mut env = mock_env();
+            env.block.height += blocks;
+            env
+        };
+        let conviction = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, blocks: u64| -> Vec<(u128, Option<u64>)> {
+            let msg = QueryMsg::Conviction { poll_id: "some_id".to_string() };
+            let res: ConvictionResponse = from_binary(&query(deps.as_ref(), at(blocks), msg).unwrap()).unwrap();
+            res.options
+                .iter()
+                .map(|o| (o.conviction.u128(), o.passed_at.map(|h| h - env.block.height)))
+                .collect()
+        };
+
+        // Con decay 0.5 la convicción de un respaldo x vale x, 1.5x, 1.75x...
+        let token = mock_info("token", &[]);
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            token.clone(), 
+            stake(ADDR1, 100, "Juno")
+        ).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), token, stake(ADDR2, 40, "Osmosis")).unwrap();
+        assert_eq!(conviction(&deps, 2), vec![(0, None), (150, None), (60, None)]);
+        assert_eq!(conviction(&deps, 3), vec![(0, None), (175, Some(3)), (70, None)]);
+
+        // Los tokens siguen bloqueados mientras respaldan una opción
+        let withdraw = ExecuteMsg::Withdraw { poll_id: "some_id".to_string() };
+        let err = execute(deps.as_mut(), at(3), info.clone(), withdraw).unwrap_err();
+        assert!(matches!(err, ContractError::PollNotClosed {}));
+
+        // addr1 pasa su respaldo a Osmosis; la convicción de Juno decae
+        let _res = execute(deps.as_mut(), at(3), info.clone(), vote("Osmosis")).unwrap();
+        let msg = QueryMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            address: ADDR1.to_string()
+        };
+        let res: VoteResponse = from_binary(&query(deps.as_ref(), at(3), msg).unwrap()).unwrap();
+        let ballot = res.vote.unwrap();
+        assert_eq!(ballot.weight, Uint128::new(100));
+        assert_eq!(ballot.since_height, Some(env.block.height + 3));
+        assert_eq!(conviction(&deps, 5), vec![(0, None), (43, Some(3)), (227, Some(4))]);
+
+        // Al cerrar ganan las opciones que alcanzaron el umbral y la convicción
+        // queda fija
+        let close = ExecuteMsg::ClosePoll { poll_id: "some_id".to_string() };
+        let res = execute(deps.as_mut(), at(5), info, close).unwrap();
+        assert!(res.attributes.contains(&attr("winners", "Juno,Osmosis")));
+        assert_eq!(conviction(&deps, 50), vec![(0, None), (43, Some(3)), (227, Some(4))]);
+    }
+
+    #[test]
+    fn test_execute_create_poll_invalid_method(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        for max_selections in [0, 3] {
+            let msg = ExecuteMsg::CreatePoll { 
+                poll_id: Some("some_id".to_string()), 
+                question: "Which chains do you use?".to_string(), 
+                options: vec![
+                    "Cosmos Hub".to_string(),
+                    "Juno".to_string()
+                ],
+                start: None,
+                end: None,
+                method: Some(VotingMethod::Approval { max_selections: Some(max_selections) }),
+                weight_source: None,
+                rules: None,
+                reveal_end: None
+            };
+            let err = execute(
+                deps.as_mut(), 
+                env.clone(), 
+                info.clone(), 
+                msg
+            ).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidVotingMethod {}));
+        }
+
+        // Aunque la configuración admita opciones repetidas, `Score` y
+        // `Ranked` no pueden distinguirlas
+        let update = ExecuteMsg::UpdateConfig { 
+            poll_limits: Some(PollLimits { unique_options: false, ..PollLimits::default() }), 
+            creation_fee: None, 
+            creation_policy: None, 
+            add_creators: None, 
+            remove_creators: None, 
+            default_poll_duration: None 
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), update).unwrap();
+        for method in [VotingMethod::Ranked, VotingMethod::Score { max_score: 5 }] {
+            let msg = ExecuteMsg::CreatePoll { 
+                poll_id: Some("some_id".to_string()), 
+                question: "Which chains do you use?".to_string(), 
+                options: vec![
+                    "Cosmos Hub".to_string(),
+                    "Juno".to_string(),
+                    "Juno".to_string()
+                ],
+                start: None,
+                end: None,
+                method: Some(method),
+                weight_source: None,
+                rules: None,
+                reveal_end: None
+            };
+            let err = execute(
+                deps.as_mut(), 
+                env.clone(), 
+                info.clone(), 
+                msg
+            ).unwrap_err();
+            assert!(matches!(err, ContractError::DuplicateOption { .. }));
+        }
+    }
+
+    #[test]
+    fn test_execute_commit_reveal(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Compromisos hasta la altura +10 y revelación hasta +20
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favorite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string(),
+                "Juno 2".to_string()
+            ],
+            start: None,
+            end: Some(Expiration::AtHeight(env.block.height + 10)),
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: Some(Expiration::AtHeight(env.block.height + 20))
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let commit = |voter: &str, vote: &str, salt: &str| ExecuteMsg::CommitVote { 
+            poll_id: "some_id".to_string(), 
+            commitment: Binary::from(
+                commitment_hash("some_id", &Addr::unchecked(voter), vote, salt)
+            )
+        };
+        let reveal = |vote: &str, salt: &str| ExecuteMsg::RevealVote { 
+            poll_id: "some_id".to_string(), 
+            vote: vote.to_string(), 
+            salt: salt.to_string()
+        };
+        let poll = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env: cosmwasm_std::Env| -> PollResponse {
+            let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap()
+        };
+        let commitments = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> CommitmentsResponse {
+            let msg = QueryMsg::Commitments { 
+                poll_id: "some_id".to_string(), 
+                start_after: None, 
+                limit: None
+            };
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // En una encuesta secreta no se vota en claro
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string()
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::SecretPoll {}));
+        let msg = ExecuteMsg::CommitVote { 
+            poll_id: "some_id".to_string(), 
+            commitment: Binary::from(b"Juno".to_vec())
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidCommitment {}));
+
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            commit(ADDR1, "Juno", "salt1")
+        ).unwrap();
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            commit(ADDR2, "Osmosis", "salt2")
+        ).unwrap();
+        // addr3 se compromete con "Juno 2"
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info("addr3", &[]), 
+            commit("addr3", "Juno 2", "xyz")
+        ).unwrap();
+        // addr4 copia el compromiso de addr1 para revelar lo mismo después
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info("addr4", &[]), 
+            commit(ADDR1, "Juno", "salt1")
+        ).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), reveal("Juno", "salt1")).unwrap_err();
+        assert!(matches!(err, ContractError::NotRevealPhase {}));
+        assert_eq!(commitments(&deps).commitments.len(), 4);
+
+        // Fase de revelación
+        let mut reveal_env = env.clone();
+        reveal_env.block.height += 15;
+        assert_eq!(poll(&deps, reveal_env.clone()).status, Some(PollStatus::Reveal));
+        let err = execute(
+            deps.as_mut(), 
+            reveal_env.clone(), 
+            info.clone(), 
+            commit(ADDR1, "Osmosis", "salt3")
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::PollClosed {}));
+        // Parte de la opción no puede pasar a la sal
+        let err = execute(
+            deps.as_mut(), 
+            reveal_env.clone(), 
+            mock_info("addr3", &[]), 
+            reveal("Juno", " 2xyz")
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidReveal {}));
+        let err = execute(
+            deps.as_mut(), 
+            reveal_env.clone(), 
+            info.clone(), 
+            reveal("Juno", "other")
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidReveal {}));
+        let _res = execute(deps.as_mut(), reveal_env.clone(), info.clone(), reveal("Juno", "salt1")).unwrap();
+        let err = execute(
+            deps.as_mut(), 
+            reveal_env.clone(), 
+            mock_info("addr4", &[]), 
+            reveal("Juno", "salt1")
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidReveal {}));
+
+        // El creador no puede congelar el resultado antes de que terminen las
+        // revelaciones
+        let close = ExecuteMsg::ClosePoll { poll_id: "some_id".to_string() };
+        let err = execute(deps.as_mut(), reveal_env.clone(), info.clone(), close.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::RevealNotFinished {}));
+
+        // Solo cuenta el voto revelado; los demás siguen como compromisos
+        let res = commitments(&deps);
+        assert_eq!(res.commitments.len(), 3);
+        assert_eq!(res.commitments[0].voter, Addr::unchecked(ADDR2));
+        let msg = QueryMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            address: ADDR2.to_string()
+        };
+        let bin = query(deps.as_ref(), reveal_env.clone(), msg).unwrap();
+        let res: VoteResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.vote, None);
+
+        // Vencida la revelación ya no se aceptan votos
+        let mut closed_env = env;
+        closed_env.block.height += 25;
+        let err = execute(
+            deps.as_mut(), 
+            closed_env.clone(), 
+            mock_info(ADDR2, &[]), 
+            reveal("Osmosis", "salt2")
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::NotRevealPhase {}));
+        let _res = execute(deps.as_mut(), closed_env.clone(), info, close).unwrap();
+        let res = poll(&deps, closed_env);
+        assert_eq!(res.status, Some(PollStatus::Closed));
+        assert_eq!(
+            res.poll.unwrap().options.iter().map(|o| o.votes.u128()).collect::<Vec<_>>(),
+            vec![0, 1, 0, 0]
+        );
+    }
+
+    // MockApi solo acepta direcciones canónicas de su propio formato; las de 20
+    // bytes derivadas de una clave pública se representan en hexadecimal
+    #[derive(Default)]
+    struct SignerApi(MockApi);
+
+    impl Api for SignerApi {
+        fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+            self.0.addr_validate(human)
+        }
+
+        fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+            self.0.addr_canonicalize(human)
+        }
+
+        fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+            match canonical.len() {
+                20 => Ok(Addr::unchecked(
+                    canonical.as_slice().iter().map(|b| format!("{:02x}", b)).collect::<String>()
+                )),
+                _ => self.0.addr_humanize(canonical),
+            }
+        }
+
+        fn secp256k1_verify(
+            &self,
+            message_hash: &[u8],
+            signature: &[u8],
+            public_key: &[u8],
+        ) -> Result<bool, VerificationError> {
+            self.0.secp256k1_verify(message_hash, signature, public_key)
+        }
+
+        fn secp256k1_recover_pubkey(
+            &self,
+            message_hash: &[u8],
+            signature: &[u8],
+            recovery_param: u8,
+        ) -> Result<Vec<u8>, RecoverPubkeyError> {
+            self.0.secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+        }
+
+        fn ed25519_verify(
+            &self,
+            message: &[u8],
+            signature: &[u8],
+            public_key: &[u8],
+        ) -> Result<bool, VerificationError> {
+            self.0.ed25519_verify(message, signature, public_key)
+        }
+
+        fn ed25519_batch_verify(
+            &self,
+            messages: &[&[u8]],
+            signatures: &[&[u8]],
+            public_keys: &[&[u8]],
+        ) -> Result<bool, VerificationError> {
+            self.0.ed25519_batch_verify(messages, signatures, public_keys)
+        }
+
+        fn debug(&self, message: &str) {
+            self.0.debug(message)
+        }
+    }
+
+    #[test]
+    fn test_execute_submit_signed_votes(){
+        let mut deps = OwnedDeps {
+            storage: MockStorage::default(),
+            api: SignerApi::default(),
+            querier: MockQuerier::default(),
+            custom_query_type: PhantomData::<Empty>,
+        };
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favorite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info, 
+            msg
+        ).unwrap();
+
+        // Dos votantes sin gas; addr2 actúa de relayer
+        let alice = SigningKey::from_bytes(&[1u8; 32]).unwrap();
+        let bob = SigningKey::from_bytes(&[2u8; 32]).unwrap();
+        let address = |key: &SigningKey| -> String {
+            Ripemd160::digest(Sha256::digest(key.verifying_key().to_bytes()))
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect()
+        };
+        let contract = env.contract.address.to_string();
+        let payload = |chain_id: &str, vote: &str, nonce: u64| -> VotePayload {
+            VotePayload { 
+                chain_id: chain_id.to_string(), 
+                contract: contract.clone(), 
+                poll_id: "some_id".to_string(), 
+                choice: Choice::Single(vote.to_string()), 
+                nonce
+            }
+        };
+        let sign = |key: &SigningKey, payload: VotePayload| -> SignedVote {
+            let signature: Signature = key.sign(&to_vec(&payload).unwrap());
+            SignedVote { 
+                payload, 
+                pubkey: Binary::from(&key.verifying_key().to_bytes()[..]), 
+                signature: Binary::from(signature.as_ref())
+            }
+        };
+        let submit = |votes: Vec<SignedVote>| ExecuteMsg::SubmitSignedVotes { votes };
+        let chain_id = env.block.chain_id.as_str();
+
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), submit(vec![])).unwrap_err();
+        assert!(matches!(err, ContractError::EmptySignedVotes {}));
+
+        let res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            submit(vec![
+                sign(&alice, payload(chain_id, "Juno", 0)),
+                sign(&bob, payload(chain_id, "Osmosis", 0))
+            ])
+        ).unwrap();
+        assert!(res.attributes.contains(&attr("voter", address(&alice))));
+        let msg = QueryMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            address: address(&alice)
+        };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: VoteResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.vote.unwrap().choice, Choice::Single("Juno".to_string()));
+        let msg = QueryMsg::Nonce { address: address(&alice) };
+        let res: NonceResponse = from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.nonce, 1);
+
+        // Repetir el mismo voto no es posible
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            submit(vec![sign(&alice, payload(chain_id, "Juno", 0))])
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidNonce { expected: 1 }));
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            submit(vec![sign(&alice, payload("other-chain", "Juno", 1))])
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidChainId {}));
+        let mut other = payload(chain_id, "Juno", 1);
+        other.contract = "other_contract".to_string();
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            submit(vec![sign(&alice, other)])
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidContract {}));
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &coins(100, "ujuno")), 
+            submit(vec![sign(&alice, payload(chain_id, "Juno", 1))])
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::UnexpectedFunds {}));
+
+        // La firma cubre todo el contenido del voto
+        let mut forged = sign(&alice, payload(chain_id, "Juno", 1));
+        forged.payload.choice = Choice::Single("Osmosis".to_string());
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), submit(vec![forged])).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSignature {}));
+        let mut forged = sign(&alice, payload(chain_id, "Juno", 1));
+        forged.pubkey = Binary::from(b"not a key".to_vec());
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), submit(vec![forged])).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPubkey {}));
+
+        // Con el nonce siguiente alice cambia su voto
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            submit(vec![sign(&alice, payload(chain_id, "Osmosis", 1))])
+        ).unwrap();
+        let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+        let res: PollResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(
+            res.poll.unwrap().options.iter().map(|o| o.votes.u128()).collect::<Vec<_>>(),
+            vec![0, 0, 2]
+        );
+    }
+
+    #[test]
+    fn test_execute_revoke_vote_valid(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favourite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string() 
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Revocamos el voto
+        let msg = ExecuteMsg::RevokeVote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string() 
+        };
+        let res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info, 
+            msg
+        ).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "revoke_vote"),
+                attr("poll_id", "some_id"),
+                attr("voter", ADDR1),
+                attr("vote", "Juno")
+            ]
+        );
+
+        // El conteo vuelve a cero y el voto desaparece
+        let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.poll.unwrap().options[1].votes, Uint128::zero());
+
+        let msg = QueryMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            address: ADDR1.to_string() 
+        };
+        let bin = query(deps.as_ref(), env, msg).unwrap();
+        let res: VoteResponse = from_binary(&bin).unwrap();
+        assert!(res.vote.is_none());
+    }
+
+    #[test]
+    fn test_execute_revoke_vote_invalid(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Revocación en una encuesta inexistente
+        let msg = ExecuteMsg::RevokeVote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string() 
+        };
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::PollNotFound {}));
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favourite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Revocación sin haber votado
+        let msg = ExecuteMsg::RevokeVote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string() 
+        };
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::BallotNotFound {}));
+
+        // Revocación de una opción distinta a la votada
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string() 
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+        let msg = ExecuteMsg::RevokeVote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Cosmos Hub".to_string() 
+        };
+        let err = execute(
+            deps.as_mut(), 
+            env, 
+            info, 
+            msg
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::VoteMismatch {}));
+    }
+
+    #[test]
+    fn test_execute_close_poll(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favourite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Empate entre Juno y Osmosis
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string() 
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Osmosis".to_string() 
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            msg
+        ).unwrap();
+
+        // Un tercero no puede cerrar la encuesta
+        let msg = ExecuteMsg::ClosePoll { poll_id: "some_id".to_string() };
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            msg.clone()
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // El creador la cierra
+        let res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg.clone()
+        ).unwrap();
+        assert_eq!(res.attributes[3], attr("winners", "Juno,Osmosis"));
+        assert_eq!(res.attributes[4], attr("total_votes", "2"));
+
+        // No se puede cerrar dos veces
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::PollClosed {}));
+
+        // Ya no se aceptan votos ni revocaciones
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Cosmos Hub".to_string() 
+        };
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::PollClosed {}));
+        let msg = ExecuteMsg::RevokeVote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string() 
+        };
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info, 
+            msg
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::PollClosed {}));
+
+        // La query devuelve el resultado congelado
+        let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.status, Some(PollStatus::Closed));
+        let result = res.poll.unwrap().result.unwrap();
+        assert_eq!(result.winners, vec!["Juno".to_string(), "Osmosis".to_string()]);
+        assert_eq!(result.total_votes, Uint128::new(2));
+        assert_eq!(result.closed_at_height, env.block.height);
+        assert_eq!(result.closed_at_time, env.block.time);
+    }
+
+    #[test]
+    fn test_execute_close_poll_ranked(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "Who should lead the committee?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string()
+            ],
+            start: None,
+            end: None,
+            method: Some(VotingMethod::Ranked),
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Cosmos Hub gana por pluralidad, pero Juno por segunda vuelta
+        let rankings: Vec<&[&str]> = vec![
+            &["Cosmos Hub"],
+            &["Cosmos Hub"],
+            &["Cosmos Hub"],
+            &["Juno", "Osmosis"],
+            &["Juno", "Osmosis"],
+            &["Osmosis", "Juno"],
+            &["Osmosis", "Juno"],
+        ];
+        for (i, ranking) in rankings.iter().enumerate() {
+            let msg = ExecuteMsg::CastBallot { 
+                poll_id: "some_id".to_string(), 
+                choice: Choice::Ranked(ranking.iter().map(|o| o.to_string()).collect()) 
+            };
+            let _res = execute(
+                deps.as_mut(), 
+                env.clone(), 
+                mock_info(&format!("voter{}", i), &[]), 
+                msg
+            ).unwrap();
+        }
+
+        // Votos ordenados inválidos
+        let msg = ExecuteMsg::CastBallot { 
+            poll_id: "some_id".to_string(), 
+            choice: Choice::Ranked(vec!["Juno".to_string(), "Juno".to_string()]) 
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::DuplicateSelection { .. }));
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string() 
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidBallotType {}));
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: "some_id".to_string() };
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // Se consulta el detalle de las rondas
+        let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+        let bin = query(deps.as_ref(), env, msg).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        let result = res.poll.unwrap().result.unwrap();
+        assert_eq!(result.winners, vec!["Juno".to_string()]);
+        assert_eq!(
+            result.rounds.unwrap(),
+            vec![
+                IrvRound {
+                    tallies: vec![
+                        PollOption { option: "Cosmos Hub".to_string(), votes: Uint128::new(3) },
+                        PollOption { option: "Juno".to_string(), votes: Uint128::new(2) },
+                        PollOption { option: "Osmosis".to_string(), votes: Uint128::new(2) }
+                    ],
+                    exhausted: Uint128::zero(),
+                    eliminated: Some("Osmosis".to_string())
+                },
+                IrvRound {
+                    tallies: vec![
+                        PollOption { option: "Cosmos Hub".to_string(), votes: Uint128::new(3) },
+                        PollOption { option: "Juno".to_string(), votes: Uint128::new(4) }
+                    ],
+                    exhausted: Uint128::zero(),
+                    eliminated: None
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_execute_close_poll_schulze(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "Who should lead the committee?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string()
+            ],
+            start: None,
+            end: None,
+            method: Some(VotingMethod::Ranked),
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let rank = |ranking: &[&str]| ExecuteMsg::CastBallot { 
+            poll_id: "some_id".to_string(), 
+            choice: Choice::Ranked(ranking.iter().map(|o| o.to_string()).collect()) 
+        };
+
+        // Cosmos Hub gana por segunda vuelta, pero Juno vence a todas por pares
+        let rankings: Vec<&[&str]> = vec![
+            &["Cosmos Hub", "Juno", "Osmosis"],
+            &["Cosmos Hub", "Juno", "Osmosis"],
+            &["Cosmos Hub", "Juno", "Osmosis"],
+            &["Cosmos Hub", "Juno", "Osmosis"],
+            &["Osmosis", "Juno", "Cosmos Hub"],
+            &["Osmosis", "Juno", "Cosmos Hub"],
+            &["Osmosis", "Juno", "Cosmos Hub"],
+            &["Juno", "Cosmos Hub", "Osmosis"],
+            &["Juno", "Cosmos Hub", "Osmosis"],
+        ];
+        for (i, ranking) in rankings.iter().enumerate() {
+            let _res = execute(
+                deps.as_mut(), 
+                env.clone(), 
+                mock_info(&format!("voter{}", i), &[]), 
+                rank(ranking)
+            ).unwrap();
+        }
+
+        // Cambiar y retirar un voto deja la matriz como estaba
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), rank(&["Osmosis"])).unwrap();
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            rank(&["Osmosis", "Cosmos Hub"])
+        ).unwrap();
+        let msg = ExecuteMsg::RevokeBallot { poll_id: "some_id".to_string() };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let as_u128 = |matrix: &[Vec<Uint128>]| -> Vec<Vec<u128>> {
+            matrix.iter().map(|row| row.iter().map(|c| c.u128()).collect()).collect()
+        };
+        let msg = QueryMsg::Pairwise { poll_id: "some_id".to_string() };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: PairwiseResponse = from_binary(&bin).unwrap();
+        assert_eq!(as_u128(&res.preferences), vec![vec![0, 4, 6], vec![5, 0, 6], vec![3, 3, 0]]);
+        assert_eq!(as_u128(&res.strongest_paths), vec![vec![0, 0, 6], vec![5, 0, 6], vec![0, 0, 0]]);
+        assert_eq!(
+            res.ranking,
+            vec![
+                vec!["Juno".to_string()],
+                vec!["Cosmos Hub".to_string()],
+                vec!["Osmosis".to_string()]
+            ]
+        );
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: "some_id".to_string() };
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+        let bin = query(deps.as_ref(), env, msg).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        let result = res.poll.unwrap().result.unwrap();
+        assert_eq!(result.winners, vec!["Cosmos Hub".to_string()]);
+        assert_eq!(result.schulze, Some(vec!["Juno".to_string()]));
+    }
+
+    #[test]
+    fn test_execute_close_poll_score(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "Rate each Cosmos coin".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string()
+            ],
+            start: None,
+            end: None,
+            method: Some(VotingMethod::Score { max_score: 5 }),
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let rate = |scores: &[(&str, u8)]| ExecuteMsg::CastBallot { 
+            poll_id: "some_id".to_string(), 
+            choice: Choice::Score(
+                scores
+                    .iter()
+                    .map(|(option, score)| OptionScore { option: option.to_string(), score: *score })
+                    .collect()
+            )
+        };
+
+        // Hay que calificar todas las opciones dentro del rango
+        let msg = rate(&[("Cosmos Hub", 5), ("Juno", 4)]);
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::IncompleteBallot {}));
+        let msg = rate(&[("Cosmos Hub", 6), ("Juno", 4), ("Osmosis", 0)]);
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidScore { .. }));
+
+        // Cosmos Hub tiene mejor mediana y Juno mejor promedio
+        for voter in [ADDR1, ADDR2] {
+            let msg = rate(&[("Cosmos Hub", 5), ("Juno", 4), ("Osmosis", 0)]);
+            let _res = execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+        let msg = rate(&[("Cosmos Hub", 3), ("Juno", 4), ("Osmosis", 1)]);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("addr3", &[]), msg).unwrap();
+        // Al cambiar el voto se descuenta la calificación anterior
+        let msg = rate(&[("Cosmos Hub", 0), ("Juno", 4), ("Osmosis", 0)]);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("addr3", &[]), msg).unwrap();
+
+        let msg = QueryMsg::ScoreHistogram { poll_id: "some_id".to_string() };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: ScoreHistogramResponse = from_binary(&bin).unwrap();
+        let hub = &res.options[0];
+        assert_eq!(hub.sum, Uint128::new(10));
+        assert_eq!(
+            hub.histogram.iter().map(|count| count.u128()).collect::<Vec<_>>(),
+            vec![1, 0, 0, 0, 0, 2]
+        );
+        assert_eq!(hub.median, Some(5));
+        assert_eq!(res.options[1].sum, Uint128::new(12));
+        assert_eq!(res.options[1].median, Some(4));
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: "some_id".to_string() };
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+        let bin = query(deps.as_ref(), env, msg).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        let result = res.poll.unwrap().result.unwrap();
+        assert_eq!(result.winners, vec!["Juno".to_string()]);
+        assert_eq!(result.majority_judgment, Some(vec!["Cosmos Hub".to_string()]));
+        assert_eq!(result.total_votes, Uint128::new(3));
+    }
+
+    #[test]
+    fn test_execute_close_poll_rules(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Quórum del 40% de un electorado de 10, mayoría simple, abstención y
+        // veto con más de un tercio
+        let rules = |threshold: Threshold, abstain: &str| PollRules {
+            quorum: Some(Quorum::Percentage { 
+                percentage: Decimal::percent(40), 
+                total_weight: Uint128::new(10)
+            }),
+            threshold,
+            abstain_option: Some(abstain.to_string()),
+            veto: Some(Veto { option: "Veto".to_string(), threshold: Decimal::from_ratio(1u128, 3u128) })
+        };
+        let create = |poll_id: &str, rules: PollRules| ExecuteMsg::CreatePoll { 
+            poll_id: Some(poll_id.to_string()), 
+            question: "Should we upgrade the chain?".to_string(), 
+            options: vec![
+                "Yes".to_string(),
+                "No".to_string(),
+                "Abstain".to_string(),
+                "Veto".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: Some(rules),
+            reveal_end: None
+        };
+
+        let msg = create("invalid", rules(Threshold::Majority {}, "Maybe"));
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPollRules {}));
+
+        // Solo con votación de opción única los votos equivalen al peso emitido
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("approval".to_string()), 
+            question: "Should we upgrade the chain?".to_string(), 
+            options: vec![
+                "Yes".to_string(),
+                "No".to_string(),
+                "Abstain".to_string(),
+                "Veto".to_string()
+            ],
+            start: None,
+            end: None,
+            method: Some(VotingMethod::Approval { max_selections: None }),
+            weight_source: None,
+            rules: Some(rules(Threshold::Majority {}, "Abstain")),
+            reveal_end: None
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPollRules {}));
+
+        let cases: Vec<(&str, Threshold, Vec<&str>, PollOutcome)> = vec![
+            ("few_voters", Threshold::Majority {}, vec!["Yes", "Yes"], PollOutcome::QuorumNotMet),
+            (
+                "passed", 
+                Threshold::Majority {}, 
+                vec!["Yes", "Yes", "Yes", "No", "Abstain", "Abstain"], 
+                PollOutcome::Passed
+            ),
+            (
+                "vetoed", 
+                Threshold::Majority {}, 
+                vec!["Yes", "Yes", "Yes", "Veto", "Veto"], 
+                PollOutcome::Rejected
+            ),
+            (
+                "supermajority", 
+                Threshold::Supermajority {}, 
+                vec!["Yes", "Yes", "Yes", "No", "No"], 
+                PollOutcome::Rejected
+            ),
+        ];
+        for (poll_id, threshold, votes, outcome) in cases {
+            let msg = create(poll_id, rules(threshold, "Abstain"));
+            let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            for (i, vote) in votes.iter().enumerate() {
+                let msg = ExecuteMsg::Vote { 
+                    poll_id: poll_id.to_string(), 
+                    vote: vote.to_string()
+                };
+                let _res = execute(
+                    deps.as_mut(), 
+                    env.clone(), 
+                    mock_info(&format!("voter{}", i), &[]), 
+                    msg
+                ).unwrap();
+            }
+
+            // Sin cerrar todavía no hay veredicto
+            let msg = QueryMsg::Poll { poll_id: poll_id.to_string() };
+            let bin = query(deps.as_ref(), env.clone(), msg.clone()).unwrap();
+            let res: PollResponse = from_binary(&bin).unwrap();
+            assert_eq!(res.outcome, None);
+
+            let close = ExecuteMsg::ClosePoll { poll_id: poll_id.to_string() };
+            let _res = execute(deps.as_mut(), env.clone(), info.clone(), close).unwrap();
+            let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+            let res: PollResponse = from_binary(&bin).unwrap();
+            assert_eq!(res.outcome, Some(outcome), "poll {}", poll_id);
+            assert_eq!(res.poll.unwrap().result.unwrap().outcome, Some(outcome));
+        }
+
+        // Un peso emitido muy superior al electorado declarado no desborda
+        let quorum = Quorum::Percentage { percentage: Decimal::percent(40), total_weight: Uint128::new(1) };
+        assert!(quorum.is_met(Uint128::MAX));
+        assert!(!quorum.is_met(Uint128::zero()));
+    }
+
+    #[test]
+    fn test_execute_delegate(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favorite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let delegate = |to: &str, poll_id: Option<&str>| ExecuteMsg::Delegate { 
+            to: to.to_string(), 
+            poll_id: poll_id.map(|p| p.to_string())
+        };
+        // addr3 -> addr2 -> addr1 de forma global; addr4 -> addr1 solo en esta
+        // encuesta; addr3 cambia a addr5 en esta encuesta
+        let delegations = vec![
+            ("addr2", delegate(ADDR1, None)),
+            ("addr3", delegate(ADDR2, None)),
+            ("addr4", delegate(ADDR1, Some("some_id"))),
+            ("addr3", delegate("addr5", Some("some_id"))),
+            ("addr6", delegate("addr5", None)),
+        ];
+        for (delegator, msg) in delegations {
+            let _res = execute(deps.as_mut(), env.clone(), mock_info(delegator, &[]), msg).unwrap();
+        }
+
+        // Ciclos, incluido delegar en uno mismo
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), delegate("addr3", None)).unwrap_err();
+        assert!(matches!(err, ContractError::DelegationCycle {}));
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), delegate(ADDR1, None)).unwrap_err();
+        assert!(matches!(err, ContractError::DelegationCycle {}));
+        // addr4 delega en addr1 solo en esta encuesta, pero una delegación
+        // global de addr1 también regiría en ella
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), delegate("addr4", None)).unwrap_err();
+        assert!(matches!(err, ContractError::DelegationCycle {}));
+
+        // addr6 retira su delegación
+        let msg = ExecuteMsg::Undelegate { poll_id: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("addr6", &[]), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr6", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::DelegationNotFound {}));
+
+        // addr1 recibe a addr2 y addr4; addr3 cuenta para addr5 en esta encuesta
+        let vote = |option: &str| ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: option.to_string()
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), vote("Cosmos Hub")).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("addr5", &[]), vote("Juno")).unwrap();
+
+        let power = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, address: &str| -> VotingPowerResponse {
+            let msg = QueryMsg::VotingPower { 
+                poll_id: "some_id".to_string(), 
+                address: address.to_string()
+            };
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+        };
+        let res = power(&deps, ADDR1);
+        assert_eq!((res.own, res.delegated, res.voted), (Uint128::new(1), Uint128::new(2), true));
+        assert_eq!(power(&deps, "addr5").delegated, Uint128::new(1));
+        assert_eq!(power(&deps, "addr3").delegate, Some("addr5".to_string()));
+
+        // Si addr2 vota, su poder ya no pasa a addr1
+        let _res = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), vote("Juno")).unwrap();
+        assert_eq!(power(&deps, ADDR1).delegated, Uint128::new(1));
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: "some_id".to_string() };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        let poll = res.poll.unwrap();
+        assert_eq!(poll.options[0].votes, Uint128::new(2));
+        assert_eq!(poll.options[1].votes, Uint128::new(3));
+        assert_eq!(poll.result.unwrap().winners, vec!["Juno".to_string()]);
+
+        // Cerrada la encuesta, la delegación de addr4 ya no forma un ciclo
+        let _res = execute(deps.as_mut(), env, info, delegate("addr4", None)).unwrap();
+    }
+
+    #[test]
+    fn test_execute_delegate_depth(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR1, &[]), 
+            msg
+        ).unwrap();
+
+        // La cadena crece por el final: addr0 -> addr1 -> ... -> addr10
+        let delegate = |i: usize| ExecuteMsg::Delegate { 
+            to: format!("addr{}", i + 1), 
+            poll_id: None
+        };
+        for i in 0..10 {
+            let sender = format!("addr{}", i);
+            let _res = execute(deps.as_mut(), env.clone(), mock_info(&sender, &[]), delegate(i)).unwrap();
+        }
+
+        // Un eslabón más dejaría a addr0 a 11 delegaciones del votante final
+        let err = execute(deps.as_mut(), env, mock_info("addr10", &[]), delegate(10)).unwrap_err();
+        assert!(matches!(err, ContractError::DelegationTooDeep {}));
+    }
+
+    #[test]
+    fn test_execute_close_poll_delegations(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favourite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+        let vote = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string() 
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info("addr10", &[]), 
+            vote.clone()
+        ).unwrap();
+
+        // Cadena de la encuesta addr0 -> ... -> addr10; una delegación global
+        // hacia addr0 queda a 11 delegaciones del votante y no suma
+        for i in 0..10 {
+            let msg = ExecuteMsg::Delegate { 
+                to: format!("addr{}", i + 1), 
+                poll_id: Some("some_id".to_string())
+            };
+            let sender = format!("addr{}", i);
+            let _res = execute(deps.as_mut(), env.clone(), mock_info(&sender, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::Delegate { to: "addr0".to_string(), poll_id: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("outsider", &[]), msg).unwrap();
+
+        // Más delegaciones de las que se resuelven en un solo cierre
+        for i in 0..MAX_DELEGATIONS_PER_CLOSE {
+            let msg = ExecuteMsg::Delegate { to: "addr10".to_string(), poll_id: None };
+            let sender = format!("delegator{}", i);
+            let _res = execute(deps.as_mut(), env.clone(), mock_info(&sender, &[]), msg).unwrap();
+        }
+
+        let close = ExecuteMsg::ClosePoll { poll_id: "some_id".to_string() };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), close.clone()).unwrap();
+        assert!(res.attributes.contains(&attr("delegations_pending", "true")));
+
+        // Mientras se resuelven, la encuesta no acepta votos ni cambios de delegación
+        let err = execute(deps.as_mut(), env.clone(), mock_info(ADDR2, &[]), vote).unwrap_err();
+        assert!(matches!(err, ContractError::PollClosed {}));
+        let msg = ExecuteMsg::Undelegate { poll_id: Some("some_id".to_string()) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr0", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::PollClosed {}));
+
+        let res = execute(deps.as_mut(), env.clone(), info, close).unwrap();
+        assert_eq!(res.attributes[3], attr("winners", "Juno"));
+        assert_eq!(res.attributes[4], attr("total_votes", "41"));
+        let msg = QueryMsg::Vote { poll_id: "some_id".to_string(), address: "addr10".to_string() };
+        let res: VoteResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(res.vote.unwrap().delegated, Uint128::new(40));
+    }
+
+    #[test]
+    fn test_execute_delete_poll_valid(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        // Instanciamos el contrato
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Creamos la encuesta
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favourite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string(),
+                "Osmosis".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Votamos desde dos direcciones distintas
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            vote: "Juno".to_string() 
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg.clone()
+        ).unwrap();
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            msg
+        ).unwrap();
+
+        // El creador elimina la encuesta
+        let msg = ExecuteMsg::DeletePoll { poll_id: "some_id".to_string() };
+        let res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info, 
+            msg
+        ).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "delete_poll"),
+                attr("poll_id", "some_id"),
+                attr("deleted_by", ADDR1),
+                attr("ballots_purged", "2")
+            ]
+        );
+
+        // La encuesta y sus votos ya no existen
+        let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        assert!(res.poll.is_none());
+
+        let msg = QueryMsg::Vote { 
+            poll_id: "some_id".to_string(), 
+            address: ADDR2.to_string() 
+        };
+        let bin = query(deps.as_ref(), env, msg).unwrap();
+        let res: VoteResponse = from_binary(&bin).unwrap();
+        assert!(res.vote.is_none());
+    }
+
+    #[test]
+    fn test_execute_delete_poll_by_admin(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        // ADDR1 es el admin, ADDR2 crea la encuesta
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR1, &[]), 
+            msg
+        ).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favourite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            msg
+        ).unwrap();
+
+        // El admin puede eliminar una encuesta ajena
+        let msg = ExecuteMsg::DeletePoll { poll_id: "some_id".to_string() };
+        let res = execute(
+            deps.as_mut(), 
+            env, 
+            mock_info(ADDR1, &[]), 
+            msg
+        ).unwrap();
+        assert_eq!(res.attributes[2], attr("deleted_by", ADDR1));
+        assert_eq!(res.attributes[3], attr("ballots_purged", "0"));
+    }
+
+    #[test]
+    fn test_execute_delete_poll_invalid(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Eliminación de una encuesta inexistente
+        let msg = ExecuteMsg::DeletePoll { poll_id: "some_id".to_string() };
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::PollNotFound {}));
+
+        let msg = ExecuteMsg::CreatePoll { 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favourite Cosmos coin?".to_string(), 
+            options: vec![
+                "Cosmos Hub".to_string(),
+                "Juno".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info, 
+            msg
+        ).unwrap();
+
+        // ADDR2 no es el creador ni el admin
+        let msg = ExecuteMsg::DeletePoll { poll_id: "some_id".to_string() };
+        let err = execute(
+            deps.as_mut(), 
+            env, 
+            mock_info(ADDR2, &[]), 
+            msg
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+    }
+
+    #[test]
+    fn test_execute_admin_transfer(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Solo el admin puede proponer un nuevo admin
+        let msg = ExecuteMsg::ProposeNewAdmin { new_admin: ADDR2.to_string() };
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            msg.clone()
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "propose_new_admin"),
+                attr("admin", ADDR1),
+                attr("pending_admin", ADDR2)
+            ]
+        );
 
+        // El admin no cambia hasta que se acepte
+        let msg = QueryMsg::ConfigUser {  };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: UserAdminResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.user_admin, Some(ADDR1.to_string()));
+        assert_eq!(res.pending_admin, Some(ADDR2.to_string()));
+
+        // Solo el admin propuesto puede aceptar
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info("addr3", &[]), 
+            ExecuteMsg::AcceptAdmin {  }
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            ExecuteMsg::AcceptAdmin {  }
+        ).unwrap();
         assert_eq!(
             res.attributes,
-            vec![attr("action", "instantiate"), attr("admin", ADDR2)]
-        )
+            vec![
+                attr("action", "accept_admin"),
+                attr("previous_admin", ADDR1),
+                attr("admin", ADDR2)
+            ]
+        );
+
+        let msg = QueryMsg::ConfigUser {  };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: UserAdminResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.user_admin, Some(ADDR2.to_string()));
+        assert_eq!(res.pending_admin, None);
+
+        // Ya no hay traspaso pendiente
+        let err = execute(
+            deps.as_mut(), 
+            env, 
+            mock_info(ADDR2, &[]), 
+            ExecuteMsg::AcceptAdmin {  }
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::NoPendingAdmin {}));
     }
 
     #[test]
-    fn test_execute_create_poll_valid(){
+    fn test_execute_renounce_admin(){
         let mut deps = mock_dependencies();
         let env = mock_env();
-        let info = mock_info(ADDR1, &vec![]);
-        // Instanciamos el contrato
-        let msg = InstantiateMsg { admin: None };
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
         let _res = instantiate(
             deps.as_mut(), 
             env.clone(), 
@@ -240,64 +5190,75 @@ mod tests {
             msg
         ).unwrap();
 
-        // msg de nueva ejecución CreatePoll
-        let msg = ExecuteMsg::CreatePoll { 
-            poll_id: "some_id".to_string(), 
-            question: "What's your favourite Cosmos coin?".to_string(), 
-            options: vec![
-                "Cosmos Hub".to_string(),
-                "Juno".to_string(),
-                "Osmosis".to_string()
-            ] 
-        };
-
-        // Unwrap para el assert
-        let _res = execute(deps.as_mut(), env, info, msg).unwrap();
-    }
-
-    #[test]
-    fn test_execute_create_poll_invalid(){
-        let mut deps = mock_dependencies();
-        let env = mock_env();
-        let info = mock_info(ADDR1, &vec![]);
-        // Instanciamos el contrato
-        let msg = InstantiateMsg{admin:None};
-        let _res = instantiate(
+        let msg = ExecuteMsg::ProposeNewAdmin { new_admin: ADDR2.to_string() };
+        let _res = execute(
             deps.as_mut(), 
             env.clone(), 
             info.clone(), 
             msg
         ).unwrap();
 
-        let msg = ExecuteMsg::CreatePoll { 
-            poll_id: "some_id".to_string(), 
-            question: "What's your favorite number?".to_string(), 
-            options: vec![
-                "1".to_string(),
-                "2".to_string(),
-                "3".to_string(),
-                "4".to_string(),
-                "5".to_string(),
-                "6".to_string(),
-                "7".to_string(),
-                "8".to_string(),
-                "9".to_string(),
-                "10".to_string(),
-                "11".to_string(),
-            ] 
+        // Con una tarifa de creación configurada no se puede renunciar
+        let update = |creation_fee: Vec<Coin>| ExecuteMsg::UpdateConfig { 
+            poll_limits: None, 
+            creation_fee: Some(creation_fee), 
+            creation_policy: None, 
+            add_creators: None, 
+            remove_creators: None, 
+            default_poll_duration: None 
         };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), update(coins(100, "ujuno"))).unwrap();
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            ExecuteMsg::RenounceAdmin {  }
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::CreationFeeWithoutAdmin {}));
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), update(vec![])).unwrap();
 
-        // Unwrap error para afirmar una falla
-        let _err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        // La renuncia también cancela el traspaso pendiente
+        let res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            ExecuteMsg::RenounceAdmin {  }
+        ).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![attr("action", "renounce_admin"), attr("previous_admin", ADDR1)]
+        );
+
+        let msg = QueryMsg::ConfigUser {  };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: UserAdminResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.user_admin, None);
+        assert_eq!(res.pending_admin, None);
+
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            ExecuteMsg::AcceptAdmin {  }
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::NoPendingAdmin {}));
+
+        // El antiguo admin ya no tiene permisos
+        let err = execute(
+            deps.as_mut(), 
+            env, 
+            info, 
+            ExecuteMsg::RenounceAdmin {  }
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
     }
 
     #[test]
-    fn test_execute_vote_valid(){
+    fn test_execute_update_config(){
         let mut deps = mock_dependencies();
         let env = mock_env();
-        let info = mock_info(ADDR1, &vec![]);
-        // Instanciando el contrato
-        let msg = InstantiateMsg{admin:None};
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
         let _res = instantiate(
             deps.as_mut(), 
             env.clone(), 
@@ -305,51 +5266,113 @@ mod tests {
             msg
         ).unwrap();
 
-        // Creacion de encuesta
-        let msg = ExecuteMsg::CreatePoll { 
-            poll_id: "some_id".to_string(), 
-            question: "What's your favorite Cosmos coin?".to_string(), 
-            options: vec![
-                "Cosmos Hub".to_string(),
-                "Juno".to_string(),
-                "Osmosis".to_string()
-            ]
+        let update = ExecuteMsg::UpdateConfig { 
+            poll_limits: Some(PollLimits { max_options: 4, ..PollLimits::default() }), 
+            creation_fee: Some(coins(100, "ujuno")), 
+            creation_policy: None, 
+            add_creators: None, 
+            remove_creators: None, 
+            default_poll_duration: Some(Some(Duration::Height(100))) 
         };
+
+        // Solo el admin puede actualizar la configuración
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            update.clone()
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
         let _res = execute(
             deps.as_mut(), 
             env.clone(), 
             info.clone(), 
-            msg
+            update
         ).unwrap();
+        let msg = QueryMsg::ConfigUser {  };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: UserAdminResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.poll_limits.max_options, 4);
+        assert_eq!(res.creation_fee, coins(100, "ujuno"));
+        assert_eq!(res.creation_policy, CreationPolicy::Anyone);
+        assert_eq!(res.default_poll_duration, Some(Duration::Height(100)));
 
-        // Creación del voto, primera votación en la encuesta
-        let msg = ExecuteMsg::Vote { 
-            poll_id: "some_id".to_string(), 
-            vote: "Juno".to_string() 
+        // Actualización parcial: los demás campos se mantienen
+        let update = ExecuteMsg::UpdateConfig { 
+            poll_limits: None, 
+            creation_fee: Some(vec![]), 
+            creation_policy: Some(CreationPolicy::Allowlist), 
+            add_creators: None, 
+            remove_creators: None, 
+            default_poll_duration: None 
         };
         let _res = execute(
             deps.as_mut(), 
             env.clone(), 
             info.clone(), 
-            msg
+            update
         ).unwrap();
+        let msg = QueryMsg::ConfigUser {  };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: UserAdminResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.poll_limits.max_options, 4);
+        assert_eq!(res.creation_fee, vec![]);
+        assert_eq!(res.creation_policy, CreationPolicy::Allowlist);
+        assert_eq!(res.default_poll_duration, Some(Duration::Height(100)));
 
-        // cambio en el voto
-        let msg = ExecuteMsg::Vote { 
-            poll_id: "some_id".to_string(), 
-            vote: "Osmosis".to_string()
-        };
-        let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+        // Valores inválidos
+        let invalid_updates = vec![
+            ExecuteMsg::UpdateConfig { 
+                poll_limits: Some(PollLimits { min_options: 1, ..PollLimits::default() }), 
+                creation_fee: None, 
+                creation_policy: None, 
+                add_creators: None, 
+                remove_creators: None, 
+                default_poll_duration: None 
+            },
+            ExecuteMsg::UpdateConfig { 
+                poll_limits: None, 
+                creation_fee: Some(coins(0, "ujuno")), 
+                creation_policy: None, 
+                add_creators: None, 
+                remove_creators: None, 
+                default_poll_duration: None 
+            },
+            ExecuteMsg::UpdateConfig { 
+                poll_limits: None, 
+                creation_fee: None, 
+                creation_policy: None, 
+                add_creators: None, 
+                remove_creators: None, 
+                default_poll_duration: Some(Some(Duration::Time(0))) 
+            },
+        ];
+        let errors: Vec<_> = invalid_updates
+            .into_iter()
+            .map(|msg| execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err())
+            .collect();
+        assert!(matches!(errors[0], ContractError::InvalidPollLimits {}));
+        assert!(matches!(errors[1], ContractError::InvalidCreationFee {}));
+        assert!(matches!(errors[2], ContractError::InvalidPollDuration {}));
 
+        // `null` elimina la duración por defecto
+        let update: ExecuteMsg = from_binary(
+            &br#"{"update_config":{"default_poll_duration":null}}"#.into()
+        ).unwrap();
+        let _res = execute(deps.as_mut(), env.clone(), info, update).unwrap();
+        let msg = QueryMsg::ConfigUser {  };
+        let bin = query(deps.as_ref(), env, msg).unwrap();
+        let res: UserAdminResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.default_poll_duration, None);
     }
 
     #[test]
-    fn test_execute_vote_invalid(){
+    fn test_execute_create_poll_with_config(){
         let mut deps = mock_dependencies();
         let env = mock_env();
-        let info = mock_info(ADDR1, &vec![]);
-        // Instanciación del contrato
-        let msg = InstantiateMsg {admin: None};
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
         let _res = instantiate(
             deps.as_mut(), 
             env.clone(), 
@@ -357,46 +5380,108 @@ mod tests {
             msg
         ).unwrap();
 
-        // Creación de voto, con some_id de una encuesta no creado aun
-        let msg = ExecuteMsg::Vote { 
-            poll_id: "some_id".to_string(), 
-            vote: "Juno".to_string() };
-        // Unwrap para afirmar el error
-        let _err = execute(
+        let update = ExecuteMsg::UpdateConfig { 
+            poll_limits: None, 
+            creation_fee: Some(coins(100, "ujuno")), 
+            creation_policy: Some(CreationPolicy::Allowlist), 
+            add_creators: None, 
+            remove_creators: None, 
+            default_poll_duration: Some(Some(Duration::Height(100))) 
+        };
+        let _res = execute(
             deps.as_mut(), 
             env.clone(), 
             info.clone(), 
-            msg
-        ).unwrap_err();
+            update
+        ).unwrap();
 
-        // Creación de encuesta
         let msg = ExecuteMsg::CreatePoll { 
-            poll_id: "some_id".to_string(), 
-            question: "What's your favourite cosmos coin?".to_string(), 
+            poll_id: Some("some_id".to_string()), 
+            question: "What's your favourite Cosmos coin?".to_string(), 
             options: vec![
                 "Cosmos Hub".to_string(),
-                "Juno".to_string(),
-                "Osmosis".to_string()
-            ] 
+                "Juno".to_string()
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
         };
-        let _res = execute(
+
+        // ADDR2 no está en la lista de creadores
+        let err = execute(
             deps.as_mut(), 
             env.clone(), 
-            info.clone(), 
-            msg
-        ).unwrap();
+            mock_info(ADDR2, &coins(100, "ujuno")), 
+            msg.clone()
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
 
-        // Voto en encuesta existente pero con opción "DVPN" inexistente
-        let msg = ExecuteMsg::Vote { 
-            poll_id: "some_id".to_string(), 
-            vote: "DVPN".to_string() 
+        let update = ExecuteMsg::UpdateConfig { 
+            poll_limits: None, 
+            creation_fee: None, 
+            creation_policy: None, 
+            add_creators: Some(vec![ADDR2.to_string()]), 
+            remove_creators: None, 
+            default_poll_duration: None 
         };
-        let _err = execute(
+        let _res = execute(
             deps.as_mut(), 
-            env, 
+            env.clone(), 
             info, 
-            msg
+            update
+        ).unwrap();
+
+        // Sin pagar la tarifa
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &coins(99, "ujuno")), 
+            msg.clone()
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientCreationFee { .. }));
+
+        // Lo que exceda la tarifa quedaría bloqueado en el contrato
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &coins(101, "ujuno")), 
+            msg.clone()
+        ).unwrap_err();
+        assert!(matches!(err, ContractError::UnexpectedFunds {}));
+        let err = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[coin(100, "ujuno"), coin(1, "uatom")]), 
+            msg.clone()
         ).unwrap_err();
+        assert!(matches!(err, ContractError::UnexpectedFunds {}));
+
+        // Pagando la tarifa, que se transfiere al admin
+        let res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &coins(100, "ujuno")), 
+            msg
+        ).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send { 
+                to_address: ADDR1.to_string(), 
+                amount: coins(100, "ujuno") 
+            })
+        );
+
+        // Se aplica la duración por defecto
+        let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        assert_eq!(
+            res.poll.unwrap().end,
+            Some(Expiration::AtHeight(env.block.height + 100))
+        );
     }
 
 // Query message testing
@@ -405,10 +5490,10 @@ mod tests {
     fn test_query_all_polls() {
         let mut deps = mock_dependencies();
         let env = mock_env();
-        let info = mock_info(ADDR1, &vec![]);
+        let info = mock_info(ADDR1, &[]);
         
         // Instantiamos el contrato
-        let msg = InstantiateMsg {admin: None};
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
         let _res = instantiate(
             deps.as_mut(), 
             env.clone(), 
@@ -417,7 +5502,7 @@ mod tests {
         );
 
         // Testing de query con  poll inexistentes
-        let msg = QueryMsg::AllPolls {  };
+        let msg = QueryMsg::AllPolls { start_after: None, limit: None, order: None };
         let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
         let res: AllPollsResponse = from_binary(&bin).unwrap();
         assert_eq!(res.polls.len(), 0);
@@ -425,13 +5510,19 @@ mod tests {
         // Testing de query con poll existentes
         // Creamos 2 encuestas de prueba: Primera encuesta
         let msg = ExecuteMsg::CreatePoll { 
-            poll_id: "some_id_1".to_string(), 
+            poll_id: Some("some_id_1".to_string()), 
             question: "What's your favorite Cosmos coin?".to_string(), 
             options: vec![
                 "Cosmos Hub".to_string(),
                 "Juno".to_string(),
                 "Osmosis".to_string()
-            ]
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
         };
         let _res = execute(
             deps.as_mut(), 
@@ -442,13 +5533,19 @@ mod tests {
 
         // Segunda encuesta
         let msg = ExecuteMsg::CreatePoll { 
-            poll_id: "some_id_2".to_string(), 
+            poll_id: Some("some_id_2".to_string()), 
             question: "What's other coin do you use?".to_string(), 
             options: vec![
                 "Near".to_string(),
                 "Bitcoin".to_string(),
                 "Ether".to_string()
-            ] 
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
         };
         let _res = execute(
             deps.as_mut(), 
@@ -458,7 +5555,7 @@ mod tests {
         ).unwrap();
 
         // Query
-        let msg = QueryMsg::AllPolls {  };
+        let msg = QueryMsg::AllPolls { start_after: None, limit: None, order: None };
         let bin = query(
             deps.as_ref(), 
             env.clone(), 
@@ -466,6 +5563,41 @@ mod tests {
         ).unwrap();
         let res: AllPollsResponse = from_binary(&bin).unwrap();
         assert_eq!(res.polls.len(), 2);
+        assert_eq!(res.polls[0].poll_id, "some_id_1");
+        assert_eq!(res.polls[1].poll_id, "some_id_2");
+
+        // Paginación ascendente
+        let msg = QueryMsg::AllPolls { 
+            start_after: Some("some_id_1".to_string()), 
+            limit: Some(1), 
+            order: None 
+        };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: AllPollsResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.polls.len(), 1);
+        assert_eq!(res.polls[0].poll_id, "some_id_2");
+        assert_eq!(res.polls[0].poll.question, "What's other coin do you use?");
+
+        // Orden descendente
+        let msg = QueryMsg::AllPolls { 
+            start_after: None, 
+            limit: Some(1), 
+            order: Some(OrderBy::Descending) 
+        };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: AllPollsResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.polls.len(), 1);
+        assert_eq!(res.polls[0].poll_id, "some_id_2");
+
+        let msg = QueryMsg::AllPolls { 
+            start_after: Some("some_id_2".to_string()), 
+            limit: None, 
+            order: Some(OrderBy::Descending) 
+        };
+        let bin = query(deps.as_ref(), env, msg).unwrap();
+        let res: AllPollsResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.polls.len(), 1);
+        assert_eq!(res.polls[0].poll_id, "some_id_1");
 
     }
 
@@ -474,9 +5606,9 @@ mod tests {
     fn test_query_poll(){
         let mut deps = mock_dependencies();
         let env = mock_env();
-        let info = mock_info(ADDR1, &vec![]);
+        let info = mock_info(ADDR1, &[]);
         // Instanciamos el contrato
-        let msg = InstantiateMsg{admin: None};
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
         let _res = instantiate(
             deps.as_mut(), 
             env.clone(), 
@@ -486,13 +5618,19 @@ mod tests {
 
         // creamos una encuesta
         let msg = ExecuteMsg::CreatePoll { 
-            poll_id: "some_id_1".to_string(), 
+            poll_id: Some("some_id_1".to_string()), 
             question: "What's your favourite Cosmos coin?".to_string(), 
             options: vec![
                 "Cosmos Hub".to_string(),
                 "Juno".to_string(),
                 "Osmosis".to_string()
-            ]
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
         };
         let _res = execute(
             deps.as_mut(), 
@@ -530,9 +5668,9 @@ mod tests {
     fn test_query_vote(){
         let mut deps = mock_dependencies();
         let env = mock_env();
-        let info = mock_info(ADDR1, &vec![]);
+        let info = mock_info(ADDR1, &[]);
         // Instanciamos el contrato
-        let msg = InstantiateMsg{admin:None};
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
         let _res = instantiate(
             deps.as_mut(), 
             env.clone(), 
@@ -542,13 +5680,19 @@ mod tests {
 
         // Creamos el poll
         let msg = ExecuteMsg::CreatePoll { 
-            poll_id: "some_id_1".to_string(), 
+            poll_id: Some("some_id_1".to_string()), 
             question: "What's your favorite Cosmos coin?".to_string(), 
             options: vec![
                 "Cosmos Hub".to_string(),
                 "Juno".to_string(),
                 "Osmosis".to_string()
-            ]
+            ],
+            start: None,
+            end: None,
+            method: None,
+            weight_source: None,
+            rules: None,
+            reveal_end: None
         };
         let _res = execute(
             deps.as_mut(), 
@@ -588,4 +5732,203 @@ mod tests {
         assert!(res.vote.is_none());
 
     }
+
+// Testing ConfigUser
+    #[test]
+    fn test_query_config_user(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: Some(ADDR2.to_string()), poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info, 
+            msg
+        ).unwrap();
+
+        let msg = QueryMsg::ConfigUser {  };
+        let bin = query(deps.as_ref(), env, msg).unwrap();
+        let res: UserAdminResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.user_admin, Some(ADDR2.to_string()));
+        assert_eq!(res.pending_admin, None);
+    }
+
+// Testing AllVoteUser
+    #[test]
+    fn test_query_all_vote_user(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info(ADDR1, &[]);
+        let msg = InstantiateMsg { admin: None, poll_limits: None };
+        let _res = instantiate(
+            deps.as_mut(), 
+            env.clone(), 
+            info.clone(), 
+            msg
+        ).unwrap();
+
+        // Creamos tres encuestas y ADDR1 vota en todas
+        for poll_id in ["poll_a", "poll_b", "poll_c"] {
+            let msg = ExecuteMsg::CreatePoll { 
+                poll_id: Some(poll_id.to_string()), 
+                question: "What's your favourite Cosmos coin?".to_string(), 
+                options: vec![
+                    "Cosmos Hub".to_string(),
+                    "Juno".to_string()
+                ],
+                start: None,
+                end: None,
+                method: None,
+                weight_source: None,
+                rules: None,
+                reveal_end: None
+            };
+            let _res = execute(
+                deps.as_mut(), 
+                env.clone(), 
+                info.clone(), 
+                msg
+            ).unwrap();
+
+            let msg = ExecuteMsg::Vote { 
+                poll_id: poll_id.to_string(), 
+                vote: "Juno".to_string() 
+            };
+            let _res = execute(
+                deps.as_mut(), 
+                env.clone(), 
+                info.clone(), 
+                msg
+            ).unwrap();
+        }
+        // ADDR2 vota solo en una
+        let msg = ExecuteMsg::Vote { 
+            poll_id: "poll_b".to_string(), 
+            vote: "Cosmos Hub".to_string() 
+        };
+        let _res = execute(
+            deps.as_mut(), 
+            env.clone(), 
+            mock_info(ADDR2, &[]), 
+            msg
+        ).unwrap();
+
+        // Primera página
+        let msg = QueryMsg::AllVoteUser { 
+            address: ADDR1.to_string(), 
+            start_after: None, 
+            limit: Some(2) 
+        };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: AllVoteUserResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.votes.len(), 2);
+        assert_eq!(res.votes[0].poll_id, "poll_a");
+        assert_eq!(res.votes[1].poll_id, "poll_b");
+        assert_eq!(res.votes[1].ballot.choice, Choice::Single("Juno".to_string()));
+
+        // Segunda página
+        let msg = QueryMsg::AllVoteUser { 
+            address: ADDR1.to_string(), 
+            start_after: Some("poll_b".to_string()), 
+            limit: Some(2) 
+        };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: AllVoteUserResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.votes.len(), 1);
+        assert_eq!(res.votes[0].poll_id, "poll_c");
+
+        // Solo aparecen los votos del usuario consultado
+        let msg = QueryMsg::AllVoteUser { 
+            address: ADDR2.to_string(), 
+            start_after: None, 
+            limit: None 
+        };
+        let bin = query(deps.as_ref(), env, msg).unwrap();
+        let res: AllVoteUserResponse = from_binary(&bin).unwrap();
+        assert_eq!(res.votes.len(), 1);
+        assert_eq!(res.votes[0].poll_id, "poll_b");
+        assert_eq!(res.votes[0].ballot.choice, Choice::Single("Cosmos Hub".to_string()));
+    }
+
+// Migration testing
+    #[test]
+    fn test_migrate_from_v0_1(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        // Estado tal como lo escribe la versión 0.1.0
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        deps.as_mut().storage.set(b"config", br#"{"admin":"addr1"}"#);
+        let poll = v0_1::Poll {
+            creator: Addr::unchecked(ADDR1),
+            question: "What's your favourite Cosmos coin?".to_string(),
+            options: vec![
+                ("Cosmos Hub".to_string(), 0),
+                ("Juno".to_string(), 2)
+            ]
+        };
+        v0_1::POLLS.save(deps.as_mut().storage, "some_id".to_string(), &poll).unwrap();
+        for voter in [ADDR1, ADDR2] {
+            v0_1::BALLOTS.save(
+                deps.as_mut().storage,
+                (Addr::unchecked(voter), "some_id".to_string()),
+                &v0_1::Ballot { option: "Juno".to_string() }
+            ).unwrap();
+        }
+
+        let res = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "migrate"),
+                attr("from_version", "0.1.0"),
+                attr("to_version", CONTRACT_VERSION),
+                attr("migrations", "0.2.0")
+            ]
+        );
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        // Las opciones se convierten a PollOption
+        let msg = QueryMsg::Poll { poll_id: "some_id".to_string() };
+        let bin = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: PollResponse = from_binary(&bin).unwrap();
+        let poll = res.poll.unwrap();
+        assert_eq!(
+            poll.options,
+            vec![
+                PollOption { option: "Cosmos Hub".to_string(), votes: Uint128::new(0) },
+                PollOption { option: "Juno".to_string(), votes: Uint128::new(2) }
+            ]
+        );
+        assert_eq!(res.status, Some(PollStatus::Open));
+
+        // El admin guardado sigue siendo válido y el índice de votos se
+        // reconstruyó: eliminar la encuesta purga ambos votos
+        let msg = ExecuteMsg::DeletePoll { poll_id: "some_id".to_string() };
+        let res = execute(deps.as_mut(), env, mock_info(ADDR1, &[]), msg).unwrap();
+        assert_eq!(res.attributes[3], attr("ballots_purged", "2"));
+    }
+
+    #[test]
+    fn test_migrate_invalid(){
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        // Contrato ajeno
+        set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidContractName { .. }));
+
+        // Versión más nueva que la del código
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let err = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap_err();
+        assert!(matches!(err, ContractError::CannotDowngrade { .. }));
+
+        // Misma versión: no hay migraciones pendientes
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, CONTRACT_VERSION).unwrap();
+        let res = migrate(deps.as_mut(), env, MigrateMsg {}).unwrap();
+        assert_eq!(res.attributes[3], attr("migrations", ""));
+    }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
 // use cw_multi_test::error;
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -7,18 +7,186 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Semver(#[from] semver::Error),
+
+    #[error("Cannot migrate from contract {previous_contract}")]
+    InvalidContractName { previous_contract: String },
+
+    #[error("Cannot migrate from newer version {previous_version}")]
+    CannotDowngrade { previous_version: String },
+
     #[error("Unauthorized")]
     Unauthorized{},
 
+    #[error("No pending admin transfer")]
+    NoPendingAdmin {},
+
     #[error("Too many poll options")]
     TooManyOptions {},
 
+    #[error("Too few poll options")]
+    TooFewOptions {},
+
+    #[error("Poll question is empty")]
+    EmptyQuestion {},
+
+    #[error("Poll question is too long")]
+    QuestionTooLong {},
+
+    #[error("Poll option is empty")]
+    EmptyOption {},
+
+    #[error("Poll option is too long: {option}")]
+    OptionTooLong { option: String },
+
+    #[error("Duplicate poll option: {option}")]
+    DuplicateOption { option: String },
+
+    #[error("Invalid poll limits")]
+    InvalidPollLimits {},
+
+    #[error("Invalid creation fee")]
+    InvalidCreationFee {},
+
+    #[error("Invalid default poll duration")]
+    InvalidPollDuration {},
+
+    #[error("Poll creation requires a fee of {fee}")]
+    InsufficientCreationFee { fee: String },
+
+    #[error("Creation fee can't be charged without an admin")]
+    CreationFeeWithoutAdmin {},
+
+    #[error("Sent funds that the contract doesn't accept")]
+    UnexpectedFunds {},
+
+    #[error("Invalid poll quorum, threshold or veto rules")]
+    InvalidPollRules {},
+
     #[error("Poll not found")]
     PollNotFound {},
 
+    #[error("Poll already exists")]
+    PollAlreadyExists {},
+
+    #[error("Invalid poll start/end window")]
+    InvalidPollWindow {},
+
+    #[error("Invalid reveal window for secret poll")]
+    InvalidRevealWindow {},
+
+    #[error("Secret poll only accepts committed votes")]
+    SecretPoll {},
+
+    #[error("Poll doesn't use secret ballots")]
+    NotSecretPoll {},
+
+    #[error("Commitment must be a sha256 hash")]
+    InvalidCommitment {},
+
+    #[error("Poll is not in its reveal phase")]
+    NotRevealPhase {},
+
+    #[error("Commitment not found")]
+    CommitmentNotFound {},
+
+    #[error("Revealed vote doesn't match the commitment")]
+    InvalidReveal {},
+
+    #[error("Secret poll can't be closed before its reveal phase ends")]
+    RevealNotFinished {},
+
+    #[error("No signed votes submitted")]
+    EmptySignedVotes {},
+
+    #[error("Invalid public key, expected a compressed secp256k1 key")]
+    InvalidPubkey {},
+
+    #[error("Invalid vote signature")]
+    InvalidSignature {},
+
+    #[error("Signed vote is for another chain")]
+    InvalidChainId {},
+
+    #[error("Signed vote is for another contract")]
+    InvalidContract {},
+
+    #[error("Invalid nonce, expected {expected}")]
+    InvalidNonce { expected: u64 },
+
+    #[error("Poll has not started yet")]
+    PollNotStarted {},
+
+    #[error("Poll is closed")]
+    PollClosed {},
+
     #[error("Option dosn't found in the poll")]
     OptionNotFound {},
 
+    #[error("Invalid voting method")]
+    InvalidVotingMethod {},
+
+    #[error("Ballot type doesn't match the poll voting method")]
+    InvalidBallotType {},
+
+    #[error("Ballot has no selections")]
+    EmptyBallot {},
+
+    #[error("Too many selections in the ballot")]
+    TooManySelections {},
+
+    #[error("Option selected more than once: {option}")]
+    DuplicateSelection { option: String },
+
+    #[error("Vote count must be positive for option: {option}")]
+    InvalidVoteCount { option: String },
+
+    #[error("Score out of range for option: {option}")]
+    InvalidScore { option: String },
+
+    #[error("Ballot must score every option")]
+    IncompleteBallot {},
+
+    #[error("Ballot costs {cost} voice credits but the budget is {budget}")]
+    InsufficientCredits { cost: Uint128, budget: Uint128 },
+
+    #[error("Funds must buy a whole number of voice credits")]
+    InvalidCreditPurchase {},
+
+    #[error("Ballot not found")]
+    BallotNotFound {},
+
+    #[error("Vote doesn't match the ballot cast")]
+    VoteMismatch {},
+
+    #[error("Voter is not a member of the poll electorate")]
+    NotEligible {},
+
+    #[error("Voter has no voting power in this poll")]
+    NoVotingPower {},
+
+    #[error("Tokens received from a cw20 contract the poll doesn't use")]
+    InvalidToken {},
+
+    #[error("Validator set must be non-empty and without duplicates")]
+    InvalidValidatorSet {},
+
+    #[error("Poll is still open")]
+    PollNotClosed {},
+
+    #[error("Nothing to withdraw")]
+    NothingToWithdraw {},
+
+    #[error("Delegation would create a cycle")]
+    DelegationCycle {},
+
+    #[error("Delegation chain is too long")]
+    DelegationTooDeep {},
+
+    #[error("Delegation not found")]
+    DelegationNotFound {},
+
     // #[error("Custom Error val: {val:?}")]
     // CustomError { val: String },
 }
@@ -1,7 +1,9 @@
 pub mod contract;
 mod error;
 pub mod helpers;
+mod migrations;
 pub mod msg;
 pub mod state;
+mod tally;
 
 pub use crate::error::ContractError;
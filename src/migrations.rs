@@ -0,0 +1,104 @@
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::Map;
+
+use crate::error::ContractError;
+use crate::state::{
+    ballots, Ballot, Choice, Poll, PollOption, VotingMethod, WeightSource, POLLS,
+};
+
+type Migration = fn(&mut dyn Storage) -> StdResult<()>;
+
+/// Migraciones de almacenamiento, en orden. Cada una se ejecuta si la versión
+/// guardada es anterior a la versión indicada.
+const MIGRATIONS: &[(&str, Migration)] = &[("0.2.0", v0_2::migrate)];
+
+/// Ejecuta las migraciones pendientes a partir de `from` y devuelve las
+/// versiones aplicadas
+pub fn run(storage: &mut dyn Storage, from: &Version) -> Result<Vec<String>, ContractError> {
+    let mut applied = vec![];
+    for (version, migration) in MIGRATIONS {
+        if *from < Version::parse(version)? {
+            migration(storage)?;
+            applied.push(version.to_string());
+        }
+    }
+    Ok(applied)
+}
+
+/// Estado escrito por la versión 0.1.0
+pub(crate) mod v0_1 {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct Poll {
+        pub creator: Addr,
+        pub question: String,
+        pub options: Vec<(String, u64)>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    pub struct Ballot {
+        pub option: String,
+    }
+
+    pub const POLLS: Map<String, Poll> = Map::new("polls");
+    // En 0.1.0 los votos no tenían índice por encuesta
+    pub const BALLOTS: Map<(Addr, String), Ballot> = Map::new("ballots");
+}
+
+/// 0.2.0: `Poll.options` pasa de tuplas a `PollOption`, los votos guardan una
+/// `Choice` con su peso y se reconstruye el índice de votos por encuesta
+mod v0_2 {
+    use super::*;
+
+    pub fn migrate(storage: &mut dyn Storage) -> StdResult<()> {
+        let polls = v0_1::POLLS
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (poll_id, poll) in polls {
+            // En 0.1.0 cada voto contaba una sola opción con peso uno
+            let voting_weight = poll.options.iter().map(|(_, votes)| Uint128::from(*votes)).sum();
+            let poll = Poll {
+                creator: poll.creator,
+                question: poll.question,
+                options: poll
+                    .options
+                    .into_iter()
+                    .map(|(option, votes)| PollOption {
+                        option,
+                        votes: Uint128::from(votes),
+                    })
+                    .collect(),
+                start: None,
+                end: None,
+                result: None,
+                method: VotingMethod::SingleChoice,
+                weight_source: WeightSource::Uniform,
+                snapshot_height: None,
+                voting_weight,
+                rules: None,
+                reveal_end: None,
+            };
+            POLLS.save(storage, poll_id, &poll)?;
+        }
+
+        let old_ballots = v0_1::BALLOTS
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (key, ballot) in old_ballots {
+            // se elimina primero el formato antiguo, que `ballots()` no puede leer
+            v0_1::BALLOTS.remove(storage, key.clone());
+            let ballot = Ballot {
+                choice: Choice::Single(ballot.option),
+                weight: Uint128::new(1),
+                delegated: Uint128::zero(),
+                since_height: None,
+            };
+            ballots().save(storage, key, &ballot)?;
+        }
+        Ok(())
+    }
+}
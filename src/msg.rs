@@ -1,27 +1,78 @@
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
-use crate::state::{Poll, Ballot};
+use serde::{Deserialize, Deserializer, Serialize};
+use cosmwasm_std::{Addr, Binary, Coin, Order, Uint128};
+use cw20::Cw20ReceiveMsg;
+use cw_utils::{Duration, Expiration, Scheduled};
+use crate::state::{
+    Ballot, Choice, CreationPolicy, Poll, PollLimits, PollOutcome, PollRules, PollStatus,
+    VotingMethod, WeightSource
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct InstantiateMsg {
     pub admin: Option<String>,
+    /// Si es `None` se usan los límites por defecto
+    pub poll_limits: Option<PollLimits>,
 }
 
+// `CreatePoll` es mucho más grande que el resto, pero el mensaje se construye
+// una sola vez por ejecución
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     CreatePoll { 
-        poll_id: String,
+        /// Si es `None`, el contrato asigna un id numérico secuencial
+        poll_id: Option<String>,
         question: String,
-        options: Vec<String>
+        options: Vec<String>,
+        start: Option<Scheduled>,
+        end: Option<Expiration>,
+        /// Si es `None`, votación de opción única
+        method: Option<VotingMethod>,
+        /// Si es `None`, un voto por dirección
+        weight_source: Option<WeightSource>,
+        /// Solo con `SingleChoice`; si es `None`, el resultado no se valida
+        rules: Option<PollRules>,
+        /// Si tiene valor la encuesta es secreta y los votos se revelan entre
+        /// `end` y `reveal_end`
+        reveal_end: Option<Expiration>
     },
 
+    /// Voto por una sola opción, equivale a `CastBallot` con `Choice::Single`
     Vote {
         poll_id: String,
         vote: String
     },
 
+    /// Voto con la forma que corresponde al método de la encuesta
+    CastBallot {
+        poll_id: String,
+        choice: Choice
+    },
+
+    /// Compromiso de voto secreto: `sha256(len(poll_id) || poll_id ||
+    /// len(votante) || votante || len(opción) || opción || sal)`, con cada
+    /// `len` en 4 bytes big-endian y los textos en UTF-8
+    CommitVote {
+        poll_id: String,
+        commitment: Binary
+    },
+
+    /// Revela un voto secreto; cuenta si coincide con el compromiso
+    RevealVote {
+        poll_id: String,
+        vote: String,
+        salt: String
+    },
+
+    /// Lote de votos firmados por sus votantes y enviados por un relayer,
+    /// que paga el gas. Si un voto falla se rechaza el lote entero
+    SubmitSignedVotes {
+        votes: Vec<SignedVote>
+    },
+
     DeletePoll {
         poll_id: String
     },
@@ -29,13 +80,112 @@ pub enum ExecuteMsg {
     RevokeVote {
         poll_id: String,
         vote: String
+    },
+
+    /// Retira el voto del remitente, sea cual sea su forma
+    RevokeBallot {
+        poll_id: String
+    },
+
+    /// Congela el resultado. Con muchas delegaciones se resuelven por tandas:
+    /// la respuesta lleva `delegations_pending` y hay que repetir el mensaje;
+    /// la encuesta ya no acepta votos desde la primera llamada
+    ClosePoll {
+        poll_id: String
+    },
+
+    /// Hook de cw20; el `msg` interno es un `ReceiveMsg`
+    Receive(Cw20ReceiveMsg),
+
+    /// Devuelve los tokens cw20 bloqueados en una encuesta ya cerrada
+    Withdraw {
+        poll_id: String
+    },
+
+    /// Delega el voto del remitente en `to`, para una encuesta o, si
+    /// `poll_id` es `None`, para todas. Votar directamente anula la delegación
+    /// en esa encuesta
+    Delegate {
+        to: String,
+        poll_id: Option<String>
+    },
+
+    Undelegate {
+        poll_id: Option<String>
+    },
+
+    ProposeNewAdmin {
+        new_admin: String
+    },
+
+    AcceptAdmin {},
+
+    RenounceAdmin {},
+
+    /// Actualización parcial de `Config`; los campos ausentes no cambian
+    UpdateConfig {
+        poll_limits: Option<PollLimits>,
+        creation_fee: Option<Vec<Coin>>,
+        creation_policy: Option<CreationPolicy>,
+        add_creators: Option<Vec<String>>,
+        remove_creators: Option<Vec<String>>,
+        /// `null` elimina la duración por defecto
+        #[serde(default, deserialize_with = "double_option")]
+        default_poll_duration: Option<Option<Duration>>
     }
 }
 
+/// Mensajes que acepta el contrato dentro de `Cw20ReceiveMsg`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// Bloquea los tokens recibidos y los suma al peso del voto
+    Vote {
+        poll_id: String,
+        choice: Choice
+    }
+}
+
+/// Voto firmado con la clave secp256k1 del votante
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SignedVote {
+    pub payload: VotePayload,
+    /// Clave pública comprimida (33 bytes); de ella se deriva la dirección del votante
+    pub pubkey: Binary,
+    /// Firma `r || s` (64 bytes) de `sha256(payload)`, con `payload` serializado en JSON
+    pub signature: Binary
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VotePayload {
+    pub chain_id: String,
+    /// Dirección de este contrato; evita reutilizar la firma en otra instancia
+    pub contract: String,
+    pub poll_id: String,
+    pub choice: Choice,
+    /// Debe coincidir con `QueryMsg::Nonce` del votante; cada voto lo incrementa
+    pub nonce: u64
+}
+
+// Distingue un campo ausente (`None`) de un `null` explícito (`Some(None)`)
+fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Deserialize::deserialize(deserializer).map(Some)
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    AllPolls {},
+    AllPolls {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<OrderBy>,
+    },
     Poll {
         poll_id: String
     },
@@ -46,18 +196,82 @@ pub enum QueryMsg {
     ConfigUser {},
     AllVoteUser {
         address: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Tokens cw20 bloqueados por `address` en la encuesta
+    Stake {
+        poll_id: String,
+        address: String
+    },
+    /// Presupuesto y gasto de créditos de voz de `address` en una encuesta cuadrática
+    VoiceCredits {
+        poll_id: String,
+        address: String
+    },
+    /// Distribución de calificaciones de cada opción en una encuesta `Score`
+    ScoreHistogram {
+        poll_id: String
+    },
+    /// Preferencias por pares y resultado de Schulze de una encuesta `Ranked`
+    Pairwise {
+        poll_id: String
+    },
+    /// Respaldo y convicción actual de cada opción en una encuesta `Conviction`
+    Conviction {
+        poll_id: String
+    },
+    /// Poder de voto propio y delegado de `address` en la encuesta
+    VotingPower {
+        poll_id: String,
+        address: String
+    },
+    /// Compromisos de voto secreto aún sin revelar
+    Commitments {
+        poll_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Nonce que debe llevar el próximo voto firmado de `address`
+    Nonce {
+        address: String
     }
     // CustomMsg { val: String },
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderBy {
+    Ascending,
+    Descending
+}
+
+impl From<OrderBy> for Order {
+    fn from(order: OrderBy) -> Order {
+        match order {
+            OrderBy::Ascending => Order::Ascending,
+            OrderBy::Descending => Order::Descending,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PollInfo {
+    pub poll_id: String,
+    pub poll: Poll
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct AllPollsResponse {
-    pub polls: Vec<Poll>,
+    pub polls: Vec<PollInfo>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct PollResponse {
-    pub poll: Option<Poll>
+    pub poll: Option<Poll>,
+    pub status: Option<PollStatus>,
+    /// Veredicto de las reglas una vez cerrada la encuesta
+    pub outcome: Option<PollOutcome>
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -65,8 +279,108 @@ pub struct VoteResponse {
     pub vote: Option<Ballot>
 }
 
-pub struct UserAdminResponse{
-    pub user_admin: String
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct UserAdminResponse {
+    pub user_admin: Option<String>,
+    pub pending_admin: Option<String>,
+    pub poll_limits: PollLimits,
+    pub creation_fee: Vec<Coin>,
+    pub creation_policy: CreationPolicy,
+    pub default_poll_duration: Option<Duration>
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct UserVote {
+    pub poll_id: String,
+    pub ballot: Ballot
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AllVoteUserResponse {
+    pub votes: Vec<UserVote>
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct StakeResponse {
+    pub amount: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct OptionHistogram {
+    pub option: String,
+    /// Suma de las calificaciones ponderadas
+    pub sum: Uint128,
+    /// Peso acumulado por calificación, de 0 a `max_score`
+    pub histogram: Vec<Uint128>,
+    /// Calificación mediana; `None` sin votos
+    pub median: Option<u8>
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ScoreHistogramResponse {
+    pub options: Vec<OptionHistogram>
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PairwiseResponse {
+    pub options: Vec<String>,
+    /// `[i][j]`: peso de los votos que prefieren `options[i]` sobre `options[j]`
+    pub preferences: Vec<Vec<Uint128>>,
+    /// `[i][j]`: fuerza del camino más fuerte de `options[i]` a `options[j]`
+    pub strongest_paths: Vec<Vec<Uint128>>,
+    /// Opciones por niveles, de ganadoras a perdedoras
+    pub ranking: Vec<Vec<String>>
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct OptionConvictionInfo {
+    pub option: String,
+    /// Peso de los votos que respaldan la opción
+    pub staked: Uint128,
+    pub conviction: Uint128,
+    /// Altura en la que la convicción alcanzó el umbral; `None` si aún no lo hizo
+    pub passed_at: Option<u64>
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ConvictionResponse {
+    /// Altura a la que se calculó la convicción: la actual o la del cierre
+    pub height: u64,
+    pub threshold: Uint128,
+    pub options: Vec<OptionConvictionInfo>
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Commitment {
+    pub voter: Addr,
+    pub commitment: Binary
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CommitmentsResponse {
+    pub commitments: Vec<Commitment>
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct VotingPowerResponse {
+    pub own: Uint128,
+    /// Poder de quienes delegan en `address`, directa o indirectamente, y no
+    /// votaron en la encuesta
+    pub delegated: Uint128,
+    /// Delegado de `address` en esta encuesta, si lo hay
+    pub delegate: Option<String>,
+    pub voted: bool
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct NonceResponse {
+    pub nonce: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct VoiceCreditsResponse {
+    pub budget: Uint128,
+    pub spent: Uint128
 }
 // #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 // #[serde(rename_all = "snake_case")]
@@ -76,4 +390,4 @@ pub struct UserAdminResponse{
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum MigrateMsg {}
+pub struct MigrateMsg {}
@@ -1,26 +1,565 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Addr;
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Binary, BlockInfo, Coin, Decimal, Empty, Timestamp, Uint128};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, KeyDeserialize, Map, MultiIndex};
+use cw_utils::{Duration, Expiration, Scheduled};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
-    pub admin: Addr
+    /// `None` una vez que el admin renuncia
+    pub admin: Option<Addr>,
+    /// Límites que debe cumplir el contenido de cada encuesta
+    #[serde(default)]
+    pub poll_limits: PollLimits,
+    /// Monto a pagar por crear una encuesta; vacío si es gratis
+    #[serde(default)]
+    pub creation_fee: Vec<Coin>,
+    #[serde(default)]
+    pub creation_policy: CreationPolicy,
+    /// Duración aplicada a las encuestas creadas sin `end`
+    #[serde(default)]
+    pub default_poll_duration: Option<Duration>
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CreationPolicy {
+    /// Cualquier dirección puede crear encuestas
+    #[default]
+    Anyone,
+    /// Solo el admin y las direcciones en `ALLOWED_CREATORS`
+    Allowlist
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollLimits {
+    pub min_options: u32,
+    pub max_options: u32,
+    /// Longitud máxima de la pregunta, en bytes
+    pub max_question_length: u32,
+    /// Longitud máxima de cada opción, en bytes
+    pub max_option_length: u32,
+    /// Rechaza encuestas con opciones repetidas
+    pub unique_options: bool
+}
+
+impl Default for PollLimits {
+    fn default() -> Self {
+        PollLimits {
+            min_options: 2,
+            max_options: 10,
+            max_question_length: 256,
+            max_option_length: 64,
+            unique_options: true
+        }
+    }
+}
+
+impl Config {
+    pub fn is_admin(&self, addr: &Addr) -> bool {
+        self.admin.as_ref() == Some(addr)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Poll {
     pub creator: Addr,
     pub question: String,
-    pub options: Vec<(String, u64)>
+    pub options: Vec<PollOption>,
+    /// Momento desde el cual se aceptan votos. Sin valor, desde la creación
+    pub start: Option<Scheduled>,
+    /// Momento en que la votación termina. Sin valor, nunca termina
+    pub end: Option<Expiration>,
+    /// Resultado congelado por `ClosePoll`
+    pub result: Option<PollResult>,
+    #[serde(default)]
+    pub method: VotingMethod,
+    #[serde(default)]
+    pub weight_source: WeightSource,
+    /// Altura de creación; los pesos con historial se consultan a esta altura
+    #[serde(default)]
+    pub snapshot_height: Option<u64>,
+    /// Peso total de los votos emitidos, cuente cada voto una o varias opciones
+    #[serde(default)]
+    pub voting_weight: Uint128,
+    /// Reglas de validez del resultado, solo en encuestas `SingleChoice`; sin
+    /// reglas solo se informan los conteos
+    #[serde(default)]
+    pub rules: Option<PollRules>,
+    /// Voto secreto: hasta `end` solo se aceptan compromisos y hasta
+    /// `reveal_end` se revelan. Solo cuentan los votos revelados
+    #[serde(default)]
+    pub reveal_end: Option<Expiration>
+}
+
+/// Reglas que deciden si el resultado de una encuesta es válido.
+///
+/// El umbral se evalúa sobre los conteos de `options`: la opción más votada
+/// (sin contar abstención ni veto) debe alcanzar la fracción exigida de los
+/// votos que no son abstención. Un empate en el primer lugar rechaza la encuesta
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollRules {
+    /// Participación mínima; sin valor no se exige quórum
+    pub quorum: Option<Quorum>,
+    pub threshold: Threshold,
+    /// Opción que cuenta para el quórum pero no para el umbral
+    pub abstain_option: Option<String>,
+    pub veto: Option<Veto>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Quorum {
+    /// Peso mínimo de votos emitidos
+    Absolute { weight: Uint128 },
+    /// Fracción mínima del peso total del electorado
+    Percentage { percentage: Decimal, total_weight: Uint128 }
+}
+
+impl Quorum {
+    pub fn is_met(&self, voting_weight: Uint128) -> bool {
+        match self {
+            Quorum::Absolute { weight } => voting_weight >= *weight,
+            Quorum::Percentage { percentage, total_weight } => {
+                ratio_reaches(voting_weight, *total_weight, *percentage)
+            }
+        }
+    }
+}
+
+// `numerator / denominator >= fraction` sin calcular el cociente, que desborda
+// `Decimal` si el peso emitido es muy superior al del electorado declarado
+fn ratio_reaches(numerator: Uint128, denominator: Uint128, fraction: Decimal) -> bool {
+    numerator.full_mul(Decimal::one().atomics()) >= fraction.atomics().full_mul(denominator)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Threshold {
+    /// Más de la mitad
+    Majority {},
+    /// Al menos dos tercios
+    Supermajority {},
+    /// Al menos `fraction`
+    Fraction { fraction: Decimal }
+}
+
+impl Threshold {
+    pub fn is_met(&self, votes: Uint128, total: Uint128) -> bool {
+        match self {
+            Threshold::Majority {} => votes.full_mul(2u64) > total.into(),
+            Threshold::Supermajority {} => votes.full_mul(3u64) >= total.full_mul(2u64),
+            Threshold::Fraction { fraction } => ratio_reaches(votes, total, *fraction),
+        }
+    }
+}
+
+/// Si los votos por `option` superan `threshold` del total, la encuesta se rechaza
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Veto {
+    pub option: String,
+    pub threshold: Decimal
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollOutcome {
+    Passed,
+    Rejected,
+    QuorumNotMet
+}
+
+/// Origen del peso de cada voto
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WeightSource {
+    /// Un voto por dirección
+    #[default]
+    Uniform,
+    /// Saldo del votante en `denom` al momento de votar
+    NativeBalance { denom: String },
+    /// Saldo del votante en el contrato cw20 `token` al momento de votar
+    Cw20Balance { token: String },
+    /// Tokens cw20 depositados en este contrato con el hook `Vote`; quedan
+    /// bloqueados hasta que la encuesta se cierra
+    Cw20Staked { token: String },
+    /// Solo votan los miembros del grupo cw4 `group`, con su peso en el grupo
+    /// a la altura de creación de la encuesta
+    Cw4Group { group: String },
+    /// Stake delegado por el votante en la denominación de staking al momento
+    /// de votar; si hay `validators`, solo cuenta el delegado a esos validadores
+    Staked { validators: Option<Vec<String>> }
+}
+
+impl WeightSource {
+    /// Si el peso debe fijarse a la altura de creación de la encuesta
+    pub fn uses_snapshot(&self) -> bool {
+        matches!(self, WeightSource::Cw4Group { .. })
+    }
+
+    /// Contrato cw20 del que depende el peso, si lo hay
+    pub fn cw20_token(&self) -> Option<&String> {
+        match self {
+            WeightSource::Cw20Balance { token } | WeightSource::Cw20Staked { token } => Some(token),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VotingMethod {
+    /// Un voto por una sola opción
+    #[default]
+    SingleChoice,
+    /// Cada votante aprueba una o varias opciones
+    Approval { max_selections: Option<u32> },
+    /// Cada votante ordena las opciones por preferencia; el resultado se
+    /// calcula por segunda vuelta instantánea al cerrar la encuesta
+    Ranked,
+    /// Cada votante reparte créditos de voz entre las opciones; `n` votos a
+    /// una opción cuestan `n²` créditos
+    Quadratic { credits: VoiceCredits },
+    /// Cada votante califica todas las opciones de 0 a `max_score`
+    Score { max_score: u8 },
+    /// Encuesta continua: cada votante respalda una opción con su peso y la
+    /// convicción de la opción se multiplica por `decay` y suma el respaldo en
+    /// cada bloque. La opción se aprueba cuando su convicción alcanza `threshold`.
+    /// Requiere `WeightSource::Cw20Staked` para que el respaldo quede bloqueado
+    Conviction { decay: Decimal, threshold: Uint128 }
+}
+
+impl VotingMethod {
+    /// Precio de cada crédito de voz si los votantes los compran al votar
+    pub fn credit_price(&self) -> Option<&Coin> {
+        match self {
+            VotingMethod::Quadratic { credits: VoiceCredits::Purchased { price } } => Some(price),
+            _ => None,
+        }
+    }
+}
+
+/// Presupuesto de créditos de voz de cada votante en una encuesta cuadrática
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceCredits {
+    /// Misma cantidad de créditos para cada votante
+    Fixed { amount: u64 },
+    /// Los créditos se compran enviando fondos al votar, a `price` cada uno
+    Purchased { price: Coin }
+}
+
+/// Convicción de las opciones de una encuesta `Conviction`, en el orden de
+/// `Poll.options`, calculada a la altura `updated_at`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConvictionState {
+    pub updated_at: u64,
+    pub options: Vec<OptionConviction>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct OptionConviction {
+    pub conviction: Uint128,
+    /// Altura en la que la convicción alcanzó el umbral
+    pub passed_at: Option<u64>
+}
+
+/// Cierre en curso de una encuesta cuyas delegaciones se resuelven por tandas
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Closing {
+    /// Altura del primer `ClosePoll`; la convicción no se acumula después
+    pub height: u64,
+    pub cursor: DelegationCursor
+}
+
+/// Última delegación resuelta al cerrar una encuesta
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DelegationCursor {
+    /// Delegaciones de la propia encuesta, después de (delegado, delegador)
+    Poll(Option<(Addr, Addr)>),
+    /// Delegaciones globales, después del delegador
+    Global(Option<Addr>)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollOption {
+    pub option: String,
+    /// Suma de los pesos de los votos recibidos; en encuestas `Score`, suma de
+    /// las calificaciones ponderadas
+    pub votes: Uint128
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollResult {
+    /// Opción(es) con más votos; más de una en caso de empate
+    pub winners: Vec<String>,
+    pub total_votes: Uint128,
+    pub closed_at_height: u64,
+    pub closed_at_time: Timestamp,
+    /// Rondas de eliminación en encuestas `Ranked`
+    pub rounds: Option<Vec<IrvRound>>,
+    /// Ganador(es) por el método de Schulze en encuestas `Ranked`
+    #[serde(default)]
+    pub schulze: Option<Vec<String>>,
+    /// Veredicto según `Poll.rules`; `None` si la encuesta no tiene reglas
+    #[serde(default)]
+    pub outcome: Option<PollOutcome>,
+    /// Ganador(es) por juicio mayoritario en encuestas `Score`; `winners`
+    /// contiene el de mayor calificación promedio
+    #[serde(default)]
+    pub majority_judgment: Option<Vec<String>>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IrvRound {
+    /// Votos de cada opción que sigue en carrera
+    pub tallies: Vec<PollOption>,
+    /// Votos sin ninguna opción en carrera
+    pub exhausted: Uint128,
+    /// Opción eliminada al final de la ronda; `None` en la ronda final
+    pub eliminated: Option<String>
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollStatus {
+    Pending,
+    Open,
+    /// Encuesta secreta en fase de revelación
+    Reveal,
+    Closed
+}
+
+impl Poll {
+    /// Estado de la encuesta calculado a partir del bloque actual
+    pub fn status(&self, block: &BlockInfo) -> PollStatus {
+        if self.result.is_some() {
+            return PollStatus::Closed;
+        }
+        if let Some(start) = self.start {
+            if !start.is_triggered(block) {
+                return PollStatus::Pending;
+            }
+        }
+        if let Some(end) = self.end {
+            if end.is_expired(block) {
+                return match self.reveal_end {
+                    Some(reveal_end) if !reveal_end.is_expired(block) => PollStatus::Reveal,
+                    _ => PollStatus::Closed,
+                };
+            }
+        }
+        PollStatus::Open
+    }
+
+    /// Calcula el resultado a partir del conteo actual de `options`
+    pub fn tally(&self, block: &BlockInfo) -> PollResult {
+        let total_votes = self.options.iter().map(|option| option.votes).sum();
+        let max = self.options.iter().map(|option| option.votes).max().unwrap_or_default();
+        let winners = if max.is_zero() {
+            vec![]
+        } else {
+            self.options
+                .iter()
+                .filter(|option| option.votes == max)
+                .map(|option| option.option.clone())
+                .collect()
+        };
+        PollResult {
+            winners,
+            total_votes,
+            closed_at_height: block.height,
+            closed_at_time: block.time,
+            rounds: None,
+            schulze: None,
+            majority_judgment: None,
+            outcome: self.outcome()
+        }
+    }
+
+    /// Veredicto de `rules` sobre los conteos actuales
+    pub fn outcome(&self) -> Option<PollOutcome> {
+        let rules = self.rules.as_ref()?;
+        if let Some(quorum) = &rules.quorum {
+            if !quorum.is_met(self.voting_weight) {
+                return Some(PollOutcome::QuorumNotMet);
+            }
+        }
+        let votes_for = |name: &str| {
+            self.options.iter().find(|o| o.option == name).map(|o| o.votes).unwrap_or_default()
+        };
+        let total: Uint128 = self.options.iter().map(|o| o.votes).sum();
+        if let Some(veto) = &rules.veto {
+            if !total.is_zero() && Decimal::from_ratio(votes_for(&veto.option), total) > veto.threshold {
+                return Some(PollOutcome::Rejected);
+            }
+        }
+
+        let abstain = rules.abstain_option.as_deref();
+        let veto = rules.veto.as_ref().map(|veto| veto.option.as_str());
+        let counted = total - abstain.map(votes_for).unwrap_or_default();
+        let candidates: Vec<Uint128> = self.options
+            .iter()
+            .filter(|o| Some(o.option.as_str()) != abstain && Some(o.option.as_str()) != veto)
+            .map(|o| o.votes)
+            .collect();
+        let max = candidates.iter().copied().max().unwrap_or_default();
+        let leaders = candidates.iter().filter(|votes| **votes == max).count();
+        if max.is_zero() || leaders > 1 || !rules.threshold.is_met(max, counted) {
+            return Some(PollOutcome::Rejected);
+        }
+        Some(PollOutcome::Passed)
+    }
+
+    /// Suma (`add`) o descuenta `weight` votos de una elección en `options`.
+    /// En votos ordenados solo cuenta la primera preferencia y en votos
+    /// cuadráticos cada opción recibe `weight` por los votos asignados.
+    /// La elección debe haber sido validada contra la encuesta
+    pub fn apply_choice(&mut self, choice: &Choice, weight: Uint128, add: bool) {
+        if add {
+            self.voting_weight += weight;
+        } else {
+            self.voting_weight -= weight;
+        }
+        let counted: Vec<(&String, Uint128)> = match choice {
+            Choice::Ranked(ranking) => ranking.iter().take(1).map(|o| (o, weight)).collect(),
+            Choice::Quadratic(votes) => votes
+                .iter()
+                .map(|v| (&v.option, weight * Uint128::from(v.votes)))
+                .collect(),
+            Choice::Score(scores) => scores
+                .iter()
+                .map(|s| (&s.option, weight * Uint128::from(s.score)))
+                .collect(),
+            _ => choice.options().into_iter().map(|o| (o, weight)).collect(),
+        };
+        for (selected, amount) in counted {
+            if let Some(option) = self.options.iter_mut().find(|o| &o.option == selected) {
+                if add {
+                    option.votes += amount;
+                } else {
+                    option.votes -= amount;
+                }
+            }
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Ballot {
-    pub option: String
+    pub choice: Choice,
+    /// Peso con el que se contó el voto; se descuenta igual al revocarlo
+    pub weight: Uint128,
+    /// Peso recibido por delegación, sumado al cerrar la encuesta
+    #[serde(default)]
+    pub delegated: Uint128,
+    /// Altura desde la que `weight` respalda la opción en encuestas `Conviction`
+    #[serde(default)]
+    pub since_height: Option<u64>
+}
+
+/// Contenido de un voto; la variante debe corresponder al `VotingMethod` de la encuesta
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Choice {
+    Single(String),
+    Approval(Vec<String>),
+    /// Opciones de mayor a menor preferencia; no es necesario ordenarlas todas
+    Ranked(Vec<String>),
+    /// Votos asignados a cada opción
+    Quadratic(Vec<QuadraticVote>),
+    /// Calificación de cada opción; deben calificarse todas
+    Score(Vec<OptionScore>)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QuadraticVote {
+    pub option: String,
+    pub votes: u64
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OptionScore {
+    pub option: String,
+    pub score: u8
+}
+
+impl Choice {
+    /// Opciones mencionadas en esta elección
+    pub fn options(&self) -> Vec<&String> {
+        match self {
+            Choice::Single(option) => vec![option],
+            Choice::Approval(options) | Choice::Ranked(options) => options.iter().collect(),
+            Choice::Quadratic(votes) => votes.iter().map(|v| &v.option).collect(),
+            Choice::Score(scores) => scores.iter().map(|s| &s.option).collect(),
+        }
+    }
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
+/// Admin propuesto que aún no acepta el traspaso
+pub const PENDING_ADMIN: Item<Addr> = Item::new("pending_admin");
+/// Direcciones habilitadas para crear encuestas con `CreationPolicy::Allowlist`
+pub const ALLOWED_CREATORS: Map<&Addr, Empty> = Map::new("allowed_creators");
 pub const POLLS: Map<String, Poll> = Map::new("polls");
-pub const BALLOTS: Map<(Addr, String), Ballot> = Map::new("ballots");
\ No newline at end of file
+/// Contador para los ids asignados por el contrato
+pub const POLL_COUNT: Item<u64> = Item::new("poll_count");
+/// Créditos de voz comprados por encuesta y votante con `VoiceCredits::Purchased`
+pub const VOICE_CREDITS: Map<(&str, &Addr), u64> = Map::new("voice_credits");
+/// Distribución de calificaciones por encuesta y opción en encuestas `Score`;
+/// la posición `i` acumula el peso de los votos con calificación `i`
+pub const SCORE_HISTOGRAMS: Map<(&str, &str), Vec<Uint128>> = Map::new("score_histograms");
+/// Matriz de preferencias por pares de las encuestas `Ranked`, en el orden de
+/// `Poll.options`: `[i][j]` es el peso de los votos que prefieren `i` sobre `j`
+pub const PAIRWISE: Map<&str, Vec<Vec<Uint128>>> = Map::new("pairwise");
+/// Convicción acumulada de las encuestas `Conviction`; se actualiza en cada
+/// cambio de votos
+pub const CONVICTION: Map<&str, ConvictionState> = Map::new("conviction");
+/// Compromisos de voto secreto por encuesta y votante; ver `ExecuteMsg::CommitVote`
+pub const COMMITMENTS: Map<(&str, &Addr), Binary> = Map::new("commitments");
+/// Delegaciones globales: delegador -> delegado
+pub const DELEGATIONS: Map<&Addr, Addr> = Map::new("delegations");
+/// Índice inverso de `DELEGATIONS`: (delegado, delegador)
+pub const DELEGATORS: Map<(&Addr, &Addr), Empty> = Map::new("delegators");
+/// Delegaciones para una sola encuesta: (delegador, encuesta) -> delegado.
+/// Tienen prioridad sobre la delegación global
+pub const POLL_DELEGATIONS: Map<(&Addr, &str), Addr> = Map::new("poll_delegations");
+/// Índice inverso de `POLL_DELEGATIONS`: (encuesta, delegado, delegador)
+pub const POLL_DELEGATORS: Map<(&str, &Addr, &Addr), Empty> = Map::new("poll_delegators");
+/// Encuestas con un `ClosePoll` que aún no resolvió todas las delegaciones
+pub const CLOSING: Map<&str, Closing> = Map::new("closing");
+/// Tokens cw20 bloqueados por encuesta y votante con `WeightSource::Cw20Staked`
+pub const STAKES: Map<(&str, &Addr), Uint128> = Map::new("stakes");
+/// Siguiente nonce que debe llevar el voto firmado de cada dirección
+pub const NONCES: Map<&Addr, u64> = Map::new("nonces");
+
+/// Índices secundarios de los votos. `poll` permite recorrer todos los votos
+/// de una encuesta sin escanear el mapa completo.
+pub struct BallotIndexes<'a> {
+    pub poll: MultiIndex<'a, String, Ballot, (Addr, String)>,
+}
+
+impl<'a> IndexList<Ballot> for BallotIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Ballot>> + '_> {
+        let v: Vec<&dyn Index<Ballot>> = vec![&self.poll];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Votos indexados por (votante, poll_id), con índice secundario por poll_id.
+pub fn ballots<'a>() -> IndexedMap<'a, (Addr, String), Ballot, BallotIndexes<'a>> {
+    let indexes = BallotIndexes {
+        poll: MultiIndex::new(
+            |pk, _| {
+                // la clave primaria siempre es un (Addr, String) válido
+                let (_, poll_id) = <(Addr, String)>::from_slice(pk).unwrap();
+                poll_id
+            },
+            "ballots",
+            "ballots__poll",
+        ),
+    };
+    IndexedMap::new("ballots", indexes)
+}
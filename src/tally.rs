@@ -0,0 +1,210 @@
+use cosmwasm_std::{Decimal, Uint128};
+
+use crate::state::{IrvRound, PollOption};
+
+/// Recuento por segunda vuelta instantánea (instant-runoff).
+///
+/// En cada ronda cada voto cuenta para su opción preferida que siga en
+/// carrera. Gana la opción con mayoría absoluta de los votos no agotados; si
+/// no la hay se elimina la opción con menos votos. Los empates en el último
+/// lugar se resuelven eliminando la opción con menos votos en la primera ronda
+/// y, si persisten, la que aparece más tarde en la encuesta. Si todas las
+/// opciones restantes empatan, todas son ganadoras.
+pub fn instant_runoff(
+    options: &[String],
+    ballots: &[(Vec<String>, Uint128)],
+) -> (Vec<String>, Vec<IrvRound>) {
+    let mut remaining: Vec<String> = options.to_vec();
+    let mut rounds: Vec<IrvRound> = vec![];
+    let mut first_round: Vec<Uint128> = vec![];
+
+    loop {
+        let mut counts = vec![Uint128::zero(); remaining.len()];
+        let mut exhausted = Uint128::zero();
+        for (ranking, weight) in ballots {
+            match ranking.iter().find_map(|o| remaining.iter().position(|r| r == o)) {
+                Some(position) => counts[position] += *weight,
+                None => exhausted += *weight,
+            }
+        }
+        if first_round.is_empty() {
+            first_round = counts.clone();
+        }
+        let active: Uint128 = counts.iter().sum();
+        let max = counts.iter().copied().max().unwrap_or_default();
+        let min = counts.iter().copied().min().unwrap_or_default();
+
+        let mut round = IrvRound {
+            tallies: remaining
+                .iter()
+                .zip(counts.iter())
+                .map(|(option, votes)| PollOption { option: option.clone(), votes: *votes })
+                .collect(),
+            exhausted,
+            eliminated: None,
+        };
+
+        // Sin votos activos no hay ganador
+        if active.is_zero() {
+            rounds.push(round);
+            return (vec![], rounds);
+        }
+        if max.full_mul(2u64) > active.into() || remaining.len() == 1 {
+            let winner = remaining[counts.iter().position(|c| *c == max).unwrap()].clone();
+            rounds.push(round);
+            return (vec![winner], rounds);
+        }
+        if max == min {
+            rounds.push(round);
+            return (remaining, rounds);
+        }
+
+        // Eliminamos la opción con menos votos
+        let loser = (0..remaining.len())
+            .filter(|i| counts[*i] == min)
+            .min_by_key(|i| {
+                let original = options.iter().position(|o| *o == remaining[*i]).unwrap();
+                (first_round[original], std::cmp::Reverse(original))
+            })
+            .unwrap();
+        round.eliminated = Some(remaining.remove(loser));
+        rounds.push(round);
+    }
+}
+
+/// Calificación mediana (inferior) de una distribución; `None` sin votos
+pub fn median_grade(histogram: &[Uint128]) -> Option<u8> {
+    let total: Uint128 = histogram.iter().sum();
+    let mut accumulated = Uint128::zero();
+    for (grade, count) in histogram.iter().enumerate() {
+        accumulated += *count;
+        if !total.is_zero() && accumulated.full_mul(2u64) >= total.into() {
+            return Some(grade as u8);
+        }
+    }
+    None
+}
+
+/// Juicio mayoritario sobre las distribuciones de calificaciones de cada opción.
+///
+/// Gana la opción con mayor mediana. Los empates se resuelven con el indicador
+/// mayoritario: si los votos por encima de la mediana (`p`) superan a los de
+/// abajo (`q`), gana quien tenga más `p`; si no, quien tenga menos `q`. Todas
+/// las opciones deben tener el mismo total de votos.
+pub fn majority_judgment(options: &[String], histograms: &[Vec<Uint128>]) -> Vec<String> {
+    let gauges: Vec<Option<(u8, bool, Uint128)>> = histograms
+        .iter()
+        .map(|histogram| {
+            let median = median_grade(histogram)?;
+            let above: Uint128 = histogram[median as usize + 1..].iter().sum();
+            let below: Uint128 = histogram[..median as usize].iter().sum();
+            Some(if above > below {
+                (median, true, above)
+            } else {
+                (median, false, Uint128::MAX - below)
+            })
+        })
+        .collect();
+    let best = match gauges.iter().flatten().max() {
+        Some(best) => *best,
+        None => return vec![],
+    };
+    options
+        .iter()
+        .zip(gauges.iter())
+        .filter(|(_, gauge)| **gauge == Some(best))
+        .map(|(option, _)| option.clone())
+        .collect()
+}
+
+/// Método de Schulze a partir de la matriz de preferencias por pares, donde
+/// `preferences[i][j]` es el peso de los votos que prefieren `i` sobre `j`.
+///
+/// Devuelve la matriz de caminos más fuertes y el orden resultante por
+/// niveles: el primer nivel son las opciones que ninguna otra vence (los
+/// ganadores), el segundo las que solo vencen las del primero, etc.
+pub fn schulze(
+    options: &[String],
+    preferences: &[Vec<Uint128>],
+) -> (Vec<Vec<Uint128>>, Vec<Vec<String>>) {
+    let n = options.len();
+    let mut paths = vec![vec![Uint128::zero(); n]; n];
+    for i in 0..n {
+        for j in 0..n {
+            if i != j && preferences[i][j] > preferences[j][i] {
+                paths[i][j] = preferences[i][j];
+            }
+        }
+    }
+    for k in 0..n {
+        for i in (0..n).filter(|i| *i != k) {
+            for j in (0..n).filter(|j| *j != k && *j != i) {
+                paths[i][j] = paths[i][j].max(paths[i][k].min(paths[k][j]));
+            }
+        }
+    }
+
+    // La relación "i vence a j" es transitiva, así que siempre hay un nivel
+    // de opciones no vencidas entre las que quedan
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut ranking = vec![];
+    while !remaining.is_empty() {
+        let (tier, rest): (Vec<usize>, Vec<usize>) = remaining
+            .iter()
+            .partition(|j| !remaining.iter().any(|i| paths[*i][**j] > paths[**j][*i]));
+        ranking.push(tier.iter().map(|i| options[*i].clone()).collect());
+        remaining = rest;
+    }
+    (paths, ranking)
+}
+
+/// Convicción tras `blocks` bloques con `staked` respaldando la opción.
+///
+/// En cada bloque la convicción se multiplica por `decay` y suma `staked`, de
+/// modo que `y(n) = y(0) * decay^n + staked * (1 + decay + ... + decay^(n-1))`.
+/// Ambos factores se calculan por duplicación, en `O(log n)` pasos.
+pub fn conviction(previous: Uint128, staked: Uint128, decay: Decimal, blocks: u64) -> Uint128 {
+    // (decay^k, 1 + decay + ... + decay^(k-1)) para el tramo acumulado y para
+    // el tramo de 2^i bloques de la iteración actual
+    let (mut power, mut sum) = (Decimal::one(), Decimal::zero());
+    let (mut step_power, mut step_sum) = (decay, Decimal::one());
+    let mut remaining = blocks;
+    while remaining > 0 {
+        if remaining & 1 == 1 {
+            sum += power * step_sum;
+            power *= step_power;
+        }
+        step_sum += step_power * step_sum;
+        step_power *= step_power;
+        remaining >>= 1;
+    }
+    previous * power + staked * sum
+}
+
+/// Primer bloque de `1..=blocks` en el que la convicción alcanza `threshold`.
+/// Con respaldo constante la convicción es monótona, así que basta una
+/// búsqueda binaria
+pub fn threshold_crossing(
+    previous: Uint128,
+    staked: Uint128,
+    decay: Decimal,
+    blocks: u64,
+    threshold: Uint128,
+) -> Option<u64> {
+    if previous >= threshold {
+        return Some(0);
+    }
+    if conviction(previous, staked, decay, blocks) < threshold {
+        return None;
+    }
+    let (mut below, mut above) = (0, blocks);
+    while above - below > 1 {
+        let middle = below + (above - below) / 2;
+        if conviction(previous, staked, decay, middle) >= threshold {
+            above = middle;
+        } else {
+            below = middle;
+        }
+    }
+    Some(above)
+}